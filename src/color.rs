@@ -0,0 +1,199 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Names of the 16 basic colors, in palette order.
+const NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// A terminal color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// Index into the 256-color palette.
+    Fixed(u8),
+    /// 24-bit color.
+    Rgb(u8, u8, u8),
+    /// The terminal's default color, which may be transparent.
+    Default,
+}
+
+impl Default for Color {
+    /// Black, for compatibility with configurations which relied on
+    /// the old numeric default. This is not `Color::Default`.
+    fn default() -> Self {
+        Color::Fixed(0)
+    }
+}
+
+impl From<u8> for Color {
+    fn from(n: u8) -> Self {
+        Color::Fixed(n)
+    }
+}
+
+/// Error returned when a string is not a valid color.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("expected a color name, number, \"#rrggbb\", \"rgb(r, g, b)\" or \"default\"")
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            let n = u32::from_str_radix(hex, 16).map_err(|_| ParseColorError)?;
+            return match hex.len() {
+                3 => {
+                    // Each digit is doubled, e.g. #f80 is #ff8800
+                    let digit = |shift: u32| ((n >> shift) & 0xf) as u8 * 0x11;
+                    Ok(Color::Rgb(digit(8), digit(4), digit(0)))
+                }
+                6 => Ok(Color::Rgb((n >> 16) as u8, (n >> 8) as u8, n as u8)),
+                _ => Err(ParseColorError),
+            };
+        }
+        if let Some(args) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+            let mut components = args.split(',').map(|c| c.trim().parse::<u8>());
+            return match (components.next(), components.next(), components.next()) {
+                (Some(Ok(r)), Some(Ok(g)), Some(Ok(b))) if components.next().is_none() => {
+                    Ok(Color::Rgb(r, g, b))
+                }
+                _ => Err(ParseColorError),
+            };
+        }
+        if s == "default" || s == "none" {
+            return Ok(Color::Default);
+        }
+        if let Some(i) = NAMES.iter().position(|&name| name == s) {
+            return Ok(Color::Fixed(i as u8));
+        }
+        s.parse().map(Color::Fixed).map_err(|_| ParseColorError)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Color::Fixed(n) => serializer.serialize_u8(n),
+            Color::Rgb(r, g, b) => {
+                serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}", r, g, b))
+            }
+            Color::Default => serializer.serialize_str("default"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl Visitor<'_> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color")
+            }
+
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<Color, E> {
+                if n <= 255 {
+                    Ok(Color::Fixed(n as u8))
+                } else {
+                    Err(E::invalid_value(
+                        Unexpected::Unsigned(n),
+                        &"an integer 0-255",
+                    ))
+                }
+            }
+
+            fn visit_i64<E: de::Error>(self, n: i64) -> Result<Color, E> {
+                if (0..=255).contains(&n) {
+                    Ok(Color::Fixed(n as u8))
+                } else {
+                    Err(E::invalid_value(Unexpected::Signed(n), &"an integer 0-255"))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Color, E> {
+                s.parse()
+                    .map_err(|e| E::custom(format!("invalid color {:?}: {}", s, e)))
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!("#ff8800".parse(), Ok(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!("#f80".parse(), Ok(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!("#ff880".parse::<Color>(), Err(ParseColorError));
+    }
+
+    #[test]
+    fn parse_rgb() {
+        assert_eq!("rgb(1, 2, 3)".parse(), Ok(Color::Rgb(1, 2, 3)));
+        assert_eq!("rgb(1, 2)".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("rgb(1, 2, 3, 4)".parse::<Color>(), Err(ParseColorError));
+        assert_eq!("rgb(256, 0, 0)".parse::<Color>(), Err(ParseColorError));
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!("red".parse(), Ok(Color::Fixed(1)));
+        assert_eq!("bright-blue".parse(), Ok(Color::Fixed(12)));
+        assert_eq!("250".parse(), Ok(Color::Fixed(250)));
+        assert_eq!("default".parse(), Ok(Color::Default));
+        assert_eq!("none".parse(), Ok(Color::Default));
+        assert_eq!("purple".parse::<Color>(), Err(ParseColorError));
+    }
+
+    #[test]
+    fn deserialize_toml() {
+        #[derive(Deserialize)]
+        struct T {
+            a: Color,
+            b: Color,
+        }
+        let t: T = toml::from_str("a = 196\nb = \"#000080\"").unwrap();
+        assert_eq!(t.a, Color::Fixed(196));
+        assert_eq!(t.b, Color::Rgb(0, 0, 0x80));
+        assert!(toml::from_str::<T>("a = 256\nb = 0").is_err());
+    }
+
+    #[test]
+    fn serialize_roundtrip() {
+        for &color in &[Color::Fixed(3), Color::Rgb(1, 2, 3), Color::Default] {
+            let json = serde_json::to_string(&color).unwrap();
+            assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
 use serde::{Deserialize, Serialize};
 
+use crate::parts;
+use crate::Color;
+
 #[derive(Default, Copy, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Style {
-    pub color: u8,
+    pub color: Color,
     pub bold: bool,
     pub italic: bool,
     pub underline: bool,
@@ -14,34 +18,305 @@ pub struct Style {
 }
 
 impl Style {
-    pub fn color(color: u8) -> Style {
+    pub fn color(color: impl Into<Color>) -> Style {
         Style {
-            color,
+            color: color.into(),
             ..Default::default()
         }
     }
 
-    pub fn bold(color: u8) -> Style {
+    pub fn bold(color: impl Into<Color>) -> Style {
         Style {
-            color,
+            color: color.into(),
             bold: true,
             ..Default::default()
         }
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+/// Name of a segment which can be placed in the layout.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SegmentKind {
+    ExitCode,
+    WorkDir,
+    Dollar,
+    Duration,
+    UserHost,
+    Jobs,
+    PythonEnv,
+    Toolchain,
+    Kube,
+    Cloud,
+    /// Git branch and status of the working directory's repository.
+    Git,
+    /// Line break, for multi-line prompts.
+    Newline,
+    /// Environment variable segment, named `env.NAME` in the layout.
+    Env(String),
+    /// External command segment, named `command.NAME` in the layout.
+    Command(String),
+}
+
+impl SegmentKind {
+    /// Names of the segments which have only one instance.
+    const NAMES: &'static [(&'static str, SegmentKind)] = &[
+        ("exit_code", SegmentKind::ExitCode),
+        ("work_dir", SegmentKind::WorkDir),
+        ("dollar", SegmentKind::Dollar),
+        ("duration", SegmentKind::Duration),
+        ("user_host", SegmentKind::UserHost),
+        ("jobs", SegmentKind::Jobs),
+        ("python_env", SegmentKind::PythonEnv),
+        ("toolchain", SegmentKind::Toolchain),
+        ("kube", SegmentKind::Kube),
+        ("cloud", SegmentKind::Cloud),
+        ("git", SegmentKind::Git),
+        ("newline", SegmentKind::Newline),
+    ];
+}
+
+impl TryFrom<String> for SegmentKind {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        if let Some(instance) = name.strip_prefix("env.") {
+            return Ok(SegmentKind::Env(instance.into()));
+        }
+        if let Some(instance) = name.strip_prefix("command.") {
+            return Ok(SegmentKind::Command(instance.into()));
+        }
+        match SegmentKind::NAMES.iter().find(|(n, _)| *n == name) {
+            Some((_, kind)) => Ok(kind.clone()),
+            None => {
+                let names: Vec<_> = SegmentKind::NAMES.iter().map(|(n, _)| *n).collect();
+                Err(format!(
+                    "unknown segment `{}`, expected one of {}, env.NAME or command.NAME",
+                    name,
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+impl From<SegmentKind> for String {
+    fn from(kind: SegmentKind) -> Self {
+        match kind {
+            SegmentKind::Env(instance) => return format!("env.{}", instance),
+            SegmentKind::Command(instance) => return format!("command.{}", instance),
+            _ => (),
+        }
+        let (name, _) = SegmentKind::NAMES
+            .iter()
+            .find(|(_, k)| *k == kind)
+            .expect("segment has no name");
+        name.to_string()
+    }
+}
+
+/// Shell which displays the prompt. This determines how non-printing
+/// escape sequences are marked.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shell {
+    /// Readline markers (`\x01`, `\x02`), which work anywhere in bash.
+    #[default]
+    Bash,
+    /// `\[`, `\]`, for output which is assigned directly to `PS1`.
+    BashPs1,
+    Zsh,
+    Fish,
+    Tcsh,
+    Powershell,
+    /// No markers at all.
+    None,
+}
+
+/// How parts of the prompt are drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenderStyle {
+    /// Background colors joined by powerline arrows.
+    #[default]
+    Powerline,
+    /// Foreground colors only.
+    Plain,
+}
+
+#[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    /// Shell which displays the prompt.
+    pub shell: Shell,
+    /// How parts of the prompt are drawn.
+    pub style: RenderStyle,
+    /// Separators between parts of the prompt.
+    pub separators: Separators,
+    /// Segments to display, in order.
+    pub layout: Vec<SegmentKind>,
+    /// Segments to display in the right-hand prompt, in order.
+    pub right_layout: Vec<SegmentKind>,
+    pub newline: Newline,
     pub dollar: Dollar,
     pub exit_code: ExitCode,
+    pub duration: Duration,
+    pub user_host: UserHost,
+    pub jobs: Jobs,
+    pub python_env: PythonEnv,
+    pub toolchain: Toolchain,
+    pub kube: Kube,
+    pub cloud: Cloud,
+    /// Environment variable segments, by name.
+    pub env: HashMap<String, Env>,
+    /// External command segments, by name.
+    pub command: HashMap<String, Command>,
     pub work_dir: WorkDir,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            shell: Default::default(),
+            style: Default::default(),
+            separators: Default::default(),
+            layout: vec![
+                SegmentKind::ExitCode,
+                SegmentKind::WorkDir,
+                SegmentKind::Dollar,
+            ],
+            right_layout: vec![],
+            newline: Default::default(),
+            dollar: Default::default(),
+            exit_code: Default::default(),
+            duration: Default::default(),
+            user_host: Default::default(),
+            jobs: Default::default(),
+            python_env: Default::default(),
+            toolchain: Default::default(),
+            kube: Default::default(),
+            cloud: Default::default(),
+            env: Default::default(),
+            command: Default::default(),
+            work_dir: Default::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Returns whether or not a segment is in either layout.
+    pub fn in_layout(&self, kind: &SegmentKind) -> bool {
+        self.layout
+            .iter()
+            .chain(&self.right_layout)
+            .any(|k| k == kind)
+    }
+
+    /// Checks that every segment in the layouts is configured.
+    pub fn validate(&self) -> Result<(), String> {
+        for kind in self.layout.iter().chain(&self.right_layout) {
+            let (table, name, exists) = match kind {
+                SegmentKind::Env(name) => ("env", name, self.env.contains_key(name)),
+                SegmentKind::Command(name) => ("command", name, self.command.contains_key(name)),
+                _ => continue,
+            };
+            if !exists {
+                return Err(format!(
+                    "segment `{0}.{1}` has no [{0}.{1}] table",
+                    table, name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Overrides how a segment is displayed for a particular value.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleOverride {
+    /// Text to display instead of the value.
+    pub label: Option<String>,
+    /// Background color.
+    pub bg: Option<Color>,
+    /// Foreground style.
+    pub sty: Option<Style>,
+}
+
+impl StyleOverride {
+    /// Applies an override, if there is one, to a segment's background
+    /// color, foreground style and label.
+    pub fn apply<'a>(
+        ov: Option<&'a StyleOverride>,
+        bg: Color,
+        sty: Style,
+        label: &'a str,
+    ) -> (Color, Style, &'a str) {
+        match ov {
+            Some(o) => (
+                o.bg.unwrap_or(bg),
+                o.sty.unwrap_or(sty),
+                o.label.as_deref().unwrap_or(label),
+            ),
+            None => (bg, sty, label),
+        }
+    }
+}
+
+/// Separators between parts of the prompt. Any which are not set
+/// default to suit the render style.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Separators {
+    /// Separator between parts with the same background color, or
+    /// between the parts of a segment in plain style.
+    pub same: Option<String>,
+    /// Separator between parts with different background colors, or
+    /// between segments in plain style.
+    pub change: Option<String>,
+    /// Separator between parts with the same background color, in the
+    /// right-hand prompt.
+    pub right_same: Option<String>,
+    /// Separator between parts with different background colors, in
+    /// the right-hand prompt.
+    pub right_change: Option<String>,
+    /// String before the prompt, or before each segment in plain
+    /// style.
+    pub left_cap: Option<String>,
+    /// String after the prompt, or after each segment in plain style.
+    pub right_cap: Option<String>,
+    /// Padding around the text of each part.
+    pub padding: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Newline {
+    /// Connector at the start of the first line, e.g. `╭─`.
+    pub first: String,
+    /// Connector at the start of lines between the first and last.
+    pub middle: String,
+    /// Connector at the start of the last line, e.g. `╰─`.
+    pub last: String,
+    /// Style of the connectors.
+    pub sty: Style,
+}
+
+impl Default for Newline {
+    fn default() -> Self {
+        Newline {
+            first: "".into(),
+            middle: "".into(),
+            last: "".into(),
+            sty: Style::color(Color::Default),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Dollar {
-    pub bg: u8,
+    pub bg: Color,
     pub user_sty: Style,
     pub root_sty: Style,
 }
@@ -49,29 +324,343 @@ pub struct Dollar {
 impl Default for Dollar {
     fn default() -> Self {
         Dollar {
-            bg: 0,
+            bg: Color::Fixed(0),
             user_sty: Style::color(15),
             root_sty: Style::bold(9),
         }
     }
 }
 
+/// How the exit code is displayed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExitCodeDisplay {
+    /// The number, e.g. `130`.
+    #[default]
+    Code,
+    /// The label or signal name if there is one, e.g. `INT`.
+    Name,
+    /// The number followed by the label, e.g. `130 INT`.
+    Both,
+    /// An icon for success or failure.
+    Icon,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct ExitCode {
-    pub success_bg: u8,
+    pub success_bg: Color,
     pub success_sty: Style,
-    pub failure_bg: u8,
+    pub failure_bg: Color,
     pub failure_sty: Style,
+
+    /// Whether or not to display the exit code when it is zero.
+    pub show_on_success: bool,
+    /// Whether to display the success icon instead of a zero exit
+    /// code.
+    pub icon_on_success: bool,
+    /// How the exit code is displayed.
+    pub display: ExitCodeDisplay,
+    /// Labels for exit codes, which take precedence over signal names.
+    pub labels: HashMap<String, String>,
+    /// Icon for success, when displaying icons.
+    pub success_icon: String,
+    /// Icon for failure, when displaying icons.
+    pub failure_icon: String,
+    /// Separator between the exit codes of a pipeline.
+    pub pipe_separator: String,
 }
 
 impl Default for ExitCode {
     fn default() -> Self {
         ExitCode {
-            success_bg: 0,
+            success_bg: Color::Fixed(0),
             success_sty: Style::bold(10),
-            failure_bg: 0,
+            failure_bg: Color::Fixed(0),
             failure_sty: Style::bold(9),
+            show_on_success: true,
+            icon_on_success: false,
+            display: Default::default(),
+            labels: Default::default(),
+            success_icon: "✔".into(),
+            failure_icon: "✘".into(),
+            pipe_separator: "|".into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Duration {
+    /// Shortest duration to display, in milliseconds.
+    pub min_ms: u64,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+}
+
+impl Default for Duration {
+    fn default() -> Self {
+        Duration {
+            min_ms: 2000,
+            bg: Color::Fixed(0),
+            sty: Style::color(11),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserHost {
+    /// Whether to display the segment even on the local machine as
+    /// the default user.
+    pub show_always: bool,
+    /// User who is not displayed on the local machine, unless
+    /// `show_always` is set.
+    pub default_user: String,
+    /// Whether to display the full host name instead of the first
+    /// component.
+    pub full_hostname: bool,
+    /// Separator between the user and host names.
+    pub separator: String,
+
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+    /// Background color inside an SSH session.
+    pub ssh_bg: Color,
+    /// Foreground style inside an SSH session.
+    pub ssh_sty: Style,
+    /// Background color when running as root.
+    pub root_bg: Color,
+    /// Foreground style when running as root.
+    pub root_sty: Style,
+}
+
+impl Default for UserHost {
+    fn default() -> Self {
+        UserHost {
+            show_always: true,
+            default_user: "".into(),
+            full_hostname: false,
+            separator: "@".into(),
+            bg: Color::Fixed(0),
+            sty: Style::color(15),
+            ssh_bg: Color::Fixed(0),
+            ssh_sty: Style::bold(11),
+            root_bg: Color::Fixed(0),
+            root_sty: Style::bold(9),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Jobs {
+    /// Indicator for one job. `{n}` is replaced by the number of jobs.
+    pub one: String,
+    /// Indicator for more than one job. `{n}` is replaced by the number
+    /// of jobs.
+    pub many: String,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Jobs {
+            one: "✦".into(),
+            many: "✦{n}".into(),
+            bg: Color::Fixed(0),
+            sty: Style::color(13),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct PythonEnv {
+    /// Prefix before the environment name.
+    pub prefix: String,
+    /// Whether or not to display the Python version.
+    pub show_version: bool,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+}
+
+impl Default for PythonEnv {
+    fn default() -> Self {
+        PythonEnv {
+            prefix: "Py:".into(),
+            show_version: false,
+            bg: Color::Fixed(24),
+            sty: Style::color(15),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Toolchain {
+    /// Icon to display for each marker file. The nearest directory,
+    /// starting from the working directory, which contains any of
+    /// these files is used.
+    pub markers: HashMap<String, String>,
+    /// Whether or not to display the version pinned by the marker
+    /// files.
+    pub show_version: bool,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+}
+
+impl Default for Toolchain {
+    fn default() -> Self {
+        Toolchain {
+            markers: parts::default_markers(),
+            show_version: true,
+            bg: Color::Fixed(238),
+            sty: Style::color(15),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Kube {
+    /// Prefix before the context name.
+    pub prefix: String,
+    /// Whether or not to display the namespace.
+    pub show_namespace: bool,
+    /// Separator between the context and namespace.
+    pub separator: String,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+    /// Overrides for particular contexts, e.g. to show production
+    /// contexts in red.
+    pub contexts: HashMap<String, StyleOverride>,
+}
+
+impl Default for Kube {
+    fn default() -> Self {
+        Kube {
+            prefix: "⎈ ".into(),
+            show_namespace: true,
+            separator: "/".into(),
+            bg: Color::Fixed(25),
+            sty: Style::color(15),
+            contexts: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cloud {
+    /// Prefix before the AWS profile.
+    pub aws_prefix: String,
+    /// Prefix before the gcloud configuration.
+    pub gcp_prefix: String,
+    /// Prefix before the Azure subscription.
+    pub azure_prefix: String,
+    /// Name of the default profile.
+    pub default_profile: String,
+    /// Whether or not to hide profiles named `default_profile`.
+    pub hide_default: bool,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+    /// Overrides for particular profiles.
+    pub profiles: HashMap<String, StyleOverride>,
+}
+
+impl Default for Cloud {
+    fn default() -> Self {
+        Cloud {
+            aws_prefix: "AWS:".into(),
+            gcp_prefix: "GCP:".into(),
+            azure_prefix: "Azure:".into(),
+            default_profile: "default".into(),
+            hide_default: true,
+            bg: Color::Fixed(214),
+            sty: Style::color(0),
+            profiles: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Env {
+    /// Name of the environment variable.
+    pub var: String,
+    /// Text to display, in which `{value}` is replaced by the value of
+    /// the variable.
+    pub format: String,
+    /// Whether or not to hide the segment when the variable is unset or
+    /// empty.
+    pub hide_if_unset: bool,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+    /// Overrides for particular values.
+    pub values: HashMap<String, StyleOverride>,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Env {
+            var: "".into(),
+            format: "{value}".into(),
+            hide_if_unset: true,
+            bg: Color::Fixed(0),
+            sty: Style::color(15),
+            values: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Command {
+    /// Program to run, followed by its arguments. The first line of its
+    /// output is displayed.
+    pub command: Vec<String>,
+    /// Longest time to wait for the program, in milliseconds. If it
+    /// takes longer, the segment is not displayed.
+    pub timeout_ms: u64,
+    /// If set, the program is only run when this file exists in the
+    /// working directory or one of its parents.
+    pub if_exists: String,
+    /// Whether or not to cache the output under `$XDG_RUNTIME_DIR` until
+    /// the directory changes.
+    pub cache: bool,
+    /// Background color.
+    pub bg: Color,
+    /// Foreground style.
+    pub sty: Style,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command {
+            command: vec![],
+            timeout_ms: 100,
+            if_exists: "".into(),
+            cache: true,
+            bg: Color::Fixed(0),
+            sty: Style::color(15),
         }
     }
 }
@@ -89,17 +678,17 @@ pub struct WorkDir {
     /// String to display when the entire path is truncated.
     pub path_trun: String,
     /// Background color of path truncation string.
-    pub path_trun_bg: u8,
+    pub path_trun_bg: Color,
     /// Foreground style of path truncation string.
     pub path_trun_sty: Style,
 
     /// Normal path component background color.
-    pub dir_bg: u8,
+    pub dir_bg: Color,
     /// Normal path component foreground style.
     pub dir_sty: Style,
 
     /// Base path component background color.
-    pub base_bg: u8,
+    pub base_bg: Color,
     /// Base path component foreground style.
     pub base_sty: Style,
 
@@ -117,11 +706,11 @@ impl Default for WorkDir {
             comp_max_len: 16,
             path_max_len: 64,
             path_trun: "...".into(),
-            path_trun_bg: 15,
+            path_trun_bg: Color::Fixed(15),
             path_trun_sty: Style::color(0),
-            dir_bg: 15,
+            dir_bg: Color::Fixed(15),
             dir_sty: Style::color(0),
-            base_bg: 15,
+            base_bg: Color::Fixed(15),
             base_sty: Style::color(0),
             git: Default::default(),
             aliases: Default::default(),
@@ -132,66 +721,193 @@ impl Default for WorkDir {
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct WorkDirGit {
-    /// Whether or not Git is enabled.
+    /// Whether or not to show the Git branch within the working
+    /// directory. It is never shown there if the `git` segment is in a
+    /// layout.
     pub enable: bool,
     /// Git branch background color.
-    pub bg: u8,
+    pub bg: Color,
     /// Git branch foreground style.
     pub sty: Style,
     /// Git branch prefix.
     pub prefix: String,
     /// Separator between branch and status.
     pub separator: String,
+    /// Prefix of the commit name when HEAD is detached.
+    pub detached_prefix: String,
+    /// Style of the commit name when HEAD is detached.
+    pub detached_sty: Style,
+    /// Whether or not to name a detached HEAD after the nearest tag,
+    /// rather than its abbreviated commit ID, if no tag points to it.
+    /// This can be slow in large repositories.
+    pub describe: bool,
 
     /// Whether or not the Git status display is enabled.
     pub status: bool,
     /// Indicator for 'current branch is ahead of upstream'.
     pub ahead: String,
+    /// Format of indicator for 'current branch is ahead of upstream', with `{n}`
+    /// replaced by the count. If set, used instead of `ahead`.
+    pub ahead_format: Option<String>,
     /// Style of indicator for 'current branch is ahead of upstream'.
     pub ahead_sty: Style,
     /// Indicator for 'current branch is behind upstream'.
     pub behind: String,
+    /// Format of indicator for 'current branch is behind upstream', with `{n}`
+    /// replaced by the count. If set, used instead of `behind`.
+    pub behind_format: Option<String>,
     /// Style of indicator for 'current branch is behind upstream'.
     pub behind_sty: Style,
     /// Indicator for 'there are file conflicts'.
     pub conflict: String,
+    /// Format of indicator for 'there are file conflicts', with `{n}`
+    /// replaced by the count. If set, used instead of `conflict`.
+    pub conflict_format: Option<String>,
     /// Style of indicator for 'there are file conflicts'.
     pub conflict_sty: Style,
     /// Indicator for 'there are staged changes'.
     pub index: String,
+    /// Format of indicator for 'there are staged changes', with `{n}`
+    /// replaced by the count. If set, used instead of `index`.
+    pub index_format: Option<String>,
     /// Style of indicator for 'there are staged changes'.
     pub index_sty: Style,
     /// Indicator for 'there are unstaged changes'.
     pub wt: String,
+    /// Format of indicator for 'there are unstaged changes', with `{n}`
+    /// replaced by the count. If set, used instead of `wt`.
+    pub wt_format: Option<String>,
     /// Style of indicator for 'there are unstaged changes'.
     pub wt_sty: Style,
     /// Indicator for 'there are untracked files'.
     pub untracked: String,
+    /// Format of indicator for 'there are untracked files', with `{n}`
+    /// replaced by the count. If set, used instead of `untracked`.
+    pub untracked_format: Option<String>,
     /// Style of indicator for 'there are untracked files'.
     pub untracked_sty: Style,
+    /// Indicator for 'there are stashed changes'.
+    pub stash: String,
+    /// Format of indicator for 'there are stashed changes', with `{n}`
+    /// replaced by the count. If set, used instead of `stash`.
+    pub stash_format: Option<String>,
+    /// Style of indicator for 'there are stashed changes'.
+    pub stash_sty: Style,
+
+    /// Label for 'a rebase is in progress', followed by the current
+    /// step if it is known.
+    pub rebase: String,
+    /// Style of label for 'a rebase is in progress'.
+    pub rebase_sty: Style,
+    /// Label for 'a merge is in progress'.
+    pub merge: String,
+    /// Style of label for 'a merge is in progress'.
+    pub merge_sty: Style,
+    /// Label for 'a cherry-pick is in progress'.
+    pub cherry_pick: String,
+    /// Style of label for 'a cherry-pick is in progress'.
+    pub cherry_pick_sty: Style,
+    /// Label for 'a bisect is in progress'.
+    pub bisect: String,
+    /// Style of label for 'a bisect is in progress'.
+    pub bisect_sty: Style,
+    /// Label for 'a revert is in progress'.
+    pub revert: String,
+    /// Style of label for 'a revert is in progress'.
+    pub revert_sty: Style,
 }
 
 impl Default for WorkDirGit {
     fn default() -> Self {
         WorkDirGit {
             enable: false,
-            bg: 250,
+            bg: Color::Fixed(250),
             sty: Style::color(0),
             prefix: "Git:".into(),
             separator: "|".into(),
+            detached_prefix: "➦".into(),
+            detached_sty: Style::color(24),
+            describe: false,
             status: false,
             ahead: "↑".into(),
+            ahead_format: None,
             ahead_sty: Style::color(19),
             behind: "↓".into(),
+            behind_format: None,
             behind_sty: Style::color(19),
             conflict: "!".into(),
+            conflict_format: None,
             conflict_sty: Style::color(0),
             index: "•".into(),
+            index_format: None,
             index_sty: Style::color(28),
             wt: "+".into(),
+            wt_format: None,
             wt_sty: Style::color(88),
             untracked: "-".into(),
+            untracked_format: None,
             untracked_sty: Style::color(88),
+            stash: "≡".into(),
+            stash_format: None,
+            stash_sty: Style::color(24),
+            rebase: "REBASE".into(),
+            rebase_sty: Style::bold(88),
+            merge: "MERGING".into(),
+            merge_sty: Style::bold(88),
+            cherry_pick: "CHERRY-PICKING".into(),
+            cherry_pick_sty: Style::bold(88),
+            bisect: "BISECTING".into(),
+            bisect_sty: Style::bold(88),
+            revert: "REVERTING".into(),
+            revert_sty: Style::bold(88),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_names() {
+        let conf: Config = toml::from_str(
+            "layout = [\"exit_code\", \"env.region\", \"newline\"]\n[env.region]\nvar = \"AWS_REGION\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            conf.layout,
+            [
+                SegmentKind::ExitCode,
+                SegmentKind::Env("region".into()),
+                SegmentKind::Newline,
+            ]
+        );
+        assert_eq!(conf.validate(), Ok(()));
+        let json = serde_json::to_string(&conf.layout).unwrap();
+        assert_eq!(json, r#"["exit_code","env.region","newline"]"#);
+    }
+
+    #[test]
+    fn git_layout_name() {
+        let conf: Config = toml::from_str("right_layout = [\"git\"]").unwrap();
+        assert_eq!(conf.right_layout, [SegmentKind::Git]);
+        assert!(conf.in_layout(&SegmentKind::Git));
+        assert!(!conf.in_layout(&SegmentKind::Jobs));
+    }
+
+    #[test]
+    fn unknown_layout_name() {
+        let err = toml::from_str::<Config>("layout = [\"foo\"]")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("unknown segment `foo`"));
+    }
+
+    #[test]
+    fn missing_instance_table() {
+        let conf: Config = toml::from_str("right_layout = [\"env.foo\"]").unwrap();
+        assert!(conf.validate().is_err());
+        let conf: Config = toml::from_str("layout = [\"command.foo\"]").unwrap();
+        assert!(conf.validate().is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Shell;
+
+/// Quotes a string for bash or zsh.
+fn quote_posix(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Quotes a string for fish.
+fn quote_fish(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Builds the integration script for `shell`, which runs `exe` to
+/// render the prompt and exports the compiled `config`, if any. If
+/// `right` is set, the right-hand prompt is set up too, where the
+/// shell has one. Returns `None` if the shell is not supported.
+pub fn script(shell: Shell, exe: &str, config: Option<&str>, right: bool) -> Option<String> {
+    let (template, right_template, quote): (&str, &str, fn(&str) -> String) = match shell {
+        Shell::Bash => (include_str!("init/bash.sh"), "", quote_posix),
+        Shell::Zsh => (
+            include_str!("init/zsh.zsh"),
+            include_str!("init/zsh-right.zsh"),
+            quote_posix,
+        ),
+        Shell::Fish => (
+            include_str!("init/fish.fish"),
+            include_str!("init/fish-right.fish"),
+            quote_fish,
+        ),
+        _ => return None,
+    };
+    let mut template = template.to_string();
+    if right {
+        template.push_str(right_template);
+    }
+    let config = match config {
+        Some(c) if shell == Shell::Fish => format!("set -gx PROMPTRESS_CONFIG {}", quote(c)),
+        Some(c) => format!("export PROMPTRESS_CONFIG={}", quote(c)),
+        None => String::new(),
+    };
+    // Substitute the config last, so that it is not scanned for
+    // placeholders
+    let script = template
+        .replace("::PROMPTRESS::", &quote(exe))
+        .replace("::CONFIG::", &config);
+    Some(script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_posix_single_quote() {
+        assert_eq!(quote_posix("it's"), r#"'it'\''s'"#);
+    }
+
+    #[test]
+    fn quote_fish_special_chars() {
+        assert_eq!(quote_fish(r"a\b'c"), r"'a\\b\'c'");
+    }
+
+    #[test]
+    fn script_unsupported_shell() {
+        assert_eq!(script(Shell::Tcsh, "promptress", None, false), None);
+    }
+
+    #[test]
+    fn script_embeds_config() {
+        let s = script(Shell::Zsh, "/bin/promptress", Some("{}"), false).unwrap();
+        assert!(s.contains("export PROMPTRESS_CONFIG='{}'\n"));
+        assert!(s.contains("__promptress_bin='/bin/promptress'\n"));
+        assert!(!s.contains("::"));
+        assert!(!s.contains("RPROMPT"));
+    }
+
+    /// Runs commands in an interactive bash with the integration script
+    /// loaded, and returns their output, or `None` if bash is missing.
+    fn run_bash(before: &str, after: &str) -> Option<String> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let init = script(Shell::Bash, "true", None, false).unwrap();
+        let mut child = Command::new("bash")
+            .args(["--norc", "--noprofile", "-i"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .env("PROMPTRESS_TEST_INIT", init)
+            .spawn()
+            .ok()?;
+        // Each line gets a prompt, so load the script in one line
+        let input = format!("{}\neval \"$PROMPTRESS_TEST_INIT\"\n{}\n", before, after);
+        child.stdin.take()?.write_all(input.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[test]
+    fn bash_duration_with_prompt_command() {
+        let out = match run_bash(
+            "PROMPT_COMMAND='sleep 0.3'\ntrap '((debug++))' DEBUG",
+            ":\necho \"$__promptress_duration $((debug > 0))\"",
+        ) {
+            Some(out) => out,
+            None => return,
+        };
+        let mut words = out.split_whitespace();
+        // The other prompt commands are not timed
+        let duration: u32 = words.next().unwrap().parse().unwrap();
+        assert!(duration < 150, "duration {}", duration);
+        // The existing DEBUG trap still runs
+        assert_eq!(words.next(), Some("1"));
+    }
+
+    #[test]
+    fn script_right_prompt() {
+        let s = script(Shell::Fish, "/bin/promptress", None, true).unwrap();
+        assert!(s.contains("function fish_right_prompt"));
+        assert!(s.contains("'/bin/promptress' --shell fish --right\n"));
+    }
+}
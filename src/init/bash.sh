@@ -0,0 +1,53 @@
+# promptress integration for bash. Load it with:
+#     eval "$(promptress init bash)"
+
+::CONFIG::
+__promptress_bin=::PROMPTRESS::
+__promptress_ready=
+
+# Marks the start of a command, for the command duration.
+__promptress_preexec() {
+    if [[ -n $__promptress_ready ]]; then
+        __promptress_ready=
+        __promptress_start=${EPOCHREALTIME:-$SECONDS.000000}
+    fi
+}
+
+# Collects the status of the last command before the prompt is drawn.
+__promptress_precmd() {
+    local code=$? pipes="${PIPESTATUS[*]}"
+    __promptress_code=$code
+    __promptress_pipestatus=$pipes
+    __promptress_duration=
+    if [[ -n $__promptress_start ]]; then
+        local now=${EPOCHREALTIME:-$SECONDS.000000}
+        # Both times are in microseconds once the decimal point is removed
+        __promptress_duration=$(( (${now//[^0-9]/} - ${__promptress_start//[^0-9]/}) / 1000 ))
+        __promptress_start=
+    fi
+}
+
+# Starts timing the next command. This runs last in PROMPT_COMMAND, so
+# that the other prompt commands are not timed.
+__promptress_arm() {
+    __promptress_ready=1
+}
+
+# Keep any existing DEBUG trap, running it after ours
+__promptress_trap=$(trap -p DEBUG)
+__promptress_trap=${__promptress_trap#trap -- }
+__promptress_trap=${__promptress_trap% DEBUG}
+eval "__promptress_trap=${__promptress_trap:-''}"
+if [[ $__promptress_trap != *__promptress_preexec* ]]; then
+    trap "__promptress_preexec${__promptress_trap:+; $__promptress_trap}" DEBUG
+fi
+unset __promptress_trap
+
+if [[ ${PROMPT_COMMAND[*]} == *__promptress_precmd* ]]; then
+    : # Already loaded
+elif [[ $(declare -p PROMPT_COMMAND 2>/dev/null) == "declare -a"* ]]; then
+    PROMPT_COMMAND=(__promptress_precmd "${PROMPT_COMMAND[@]}" __promptress_arm)
+else
+    PROMPT_COMMAND="__promptress_precmd${PROMPT_COMMAND:+;$PROMPT_COMMAND};__promptress_arm"
+fi
+PS1='$(PROMPTRESS_EXIT_CODE="$__promptress_code" PROMPTRESS_PIPESTATUS="$__promptress_pipestatus" PROMPTRESS_DURATION_MS="$__promptress_duration" PROMPTRESS_JOBS=\j "$__promptress_bin" --shell bash)'
@@ -0,0 +1,11 @@
+
+function fish_right_prompt
+    set -l last_pipestatus $pipestatus
+    set -l last_status $status
+    set -l jobs (jobs -p | count)
+    PROMPTRESS_EXIT_CODE=$last_status \
+    PROMPTRESS_PIPESTATUS="$last_pipestatus" \
+    PROMPTRESS_DURATION_MS=$CMD_DURATION \
+    PROMPTRESS_JOBS=$jobs \
+    ::PROMPTRESS:: --shell fish --right
+end
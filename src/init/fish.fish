@@ -0,0 +1,15 @@
+# promptress integration for fish. Load it with:
+#     promptress init fish | source
+
+::CONFIG::
+function fish_prompt
+    # `set` keeps $status intact, so both can be collected here
+    set -l last_pipestatus $pipestatus
+    set -l last_status $status
+    set -l jobs (jobs -p | count)
+    PROMPTRESS_EXIT_CODE=$last_status \
+    PROMPTRESS_PIPESTATUS="$last_pipestatus" \
+    PROMPTRESS_DURATION_MS=$CMD_DURATION \
+    PROMPTRESS_JOBS=$jobs \
+    ::PROMPTRESS:: --shell fish
+end
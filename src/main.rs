@@ -2,22 +2,40 @@ use std::env;
 use std::env::VarError;
 use std::fs::File;
 use std::io;
-use std::io::Read;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
+use serde::de::value::StrDeserializer;
+use serde::Deserialize;
+
+pub use crate::color::Color;
 pub use crate::config::*;
+pub use crate::parts::Segment;
 pub use crate::prompt::Prompt;
 
+mod color;
 mod config;
+mod init;
 pub mod parts;
 mod prompt;
 
-fn display_prompt(mut p: Prompt) {
-    parts::exit_code(&mut p);
-    parts::work_dir(&mut p);
-    parts::dollar(&mut p);
-    p.finish();
+fn display_prompt(conf: &Config, right: bool) {
+    let (mut p, layout) = if right {
+        (Prompt::new_right(conf), &conf.right_layout)
+    } else {
+        (Prompt::new(conf), &conf.layout)
+    };
+    p.render(layout);
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    if let Err(e) = stdout
+        .write_all(p.into_string().as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        eprintln!("promptress: cannot write prompt: {}", e);
+        process::exit(1);
+    }
 }
 
 /// Reads from a file. If this fails, prints the error and exits the
@@ -42,57 +60,159 @@ fn read_file(filename: impl AsRef<Path>) -> String {
     s
 }
 
-fn main() {
-    let args: Vec<_> = env::args().skip(1).collect();
-    if args.is_empty() {
-        // Display the prompt
-        match env::var("PROMPTRESS_CONFIG") {
-            Ok(conf) => {
-                let config: Config = match serde_json::from_str(&conf) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        eprintln!("promptress: cannot parse PROMPTRESS_CONFIG: {}", e);
-                        process::exit(1);
-                    }
-                };
-                display_prompt(Prompt::new(config));
+/// Prints the usage message and exits the program.
+fn usage() -> ! {
+    eprintln!("Usage: promptress [--shell SHELL] [--right] [FILE]");
+    eprintln!("       promptress init SHELL");
+    eprintln!("Without FILE, render the prompt, or the right-hand prompt with --right.");
+    eprintln!("With FILE, compile the file configuration.");
+    eprintln!("With init, print the integration script for SHELL.");
+    eprintln!("SHELL is one of bash, bash-ps1, zsh, fish, tcsh, powershell, none.");
+    process::exit(2);
+}
+
+/// Parses a shell name from the command line. If this fails, prints
+/// the error and exits the program.
+fn parse_shell(name: &str) -> Shell {
+    let de = StrDeserializer::<serde::de::value::Error>::new(name);
+    match Shell::deserialize(de) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("promptress: invalid shell: {}", e);
+            process::exit(2);
+        }
+    }
+}
+
+/// Returns the path of the user's configuration file.
+fn config_path() -> PathBuf {
+    let mut path = dirs::home_dir().expect("cannot get home dir");
+    path.push(".promptress.toml");
+    path
+}
+
+/// Reads a configuration file. If this fails, prints the error and
+/// exits the program.
+fn compile_config(filename: impl AsRef<Path>) -> Config {
+    let filename = filename.as_ref();
+    let s = read_file(filename);
+    let config: Config = match toml::from_str(&s) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("promptress: cannot parse {}: {}", filename.display(), e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = config.validate() {
+        eprintln!(
+            "promptress: invalid config in {}: {}",
+            filename.display(),
+            e
+        );
+        process::exit(1);
+    }
+    config
+}
+
+/// Loads the configuration to render the prompt with, either from
+/// `PROMPTRESS_CONFIG` or from `~/.promptress.toml`.
+fn load_config() -> Config {
+    match env::var("PROMPTRESS_CONFIG") {
+        Ok(conf) => {
+            let config: Config = match serde_json::from_str(&conf) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("promptress: cannot parse PROMPTRESS_CONFIG: {}", e);
+                    process::exit(1);
+                }
+            };
+            if let Err(e) = config.validate() {
+                eprintln!("promptress: invalid config in PROMPTRESS_CONFIG: {}", e);
+                process::exit(1);
             }
-            Err(VarError::NotPresent) => {
-                // If PROMPTRESS_CONFIG is not set, then read from .promptress.toml
-                let mut path = dirs::home_dir().expect("cannot get home dir");
-                path.push(".promptress.toml");
-                let conf = read_file(&path);
-                let config: Config = match toml::from_str(&conf) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        eprintln!("promptress: cannot parse {}: {}", path.display(), e);
-                        process::exit(1);
-                    }
-                };
-                display_prompt(Prompt::new(config));
+            config
+        }
+        Err(VarError::NotPresent) => {
+            // If PROMPTRESS_CONFIG is not set, then read from .promptress.toml
+            compile_config(config_path())
+        }
+        Err(VarError::NotUnicode(_)) => {
+            // Invalid Unicode -- just print the error and exit
+            eprintln!("promptress: PROMPTRESS_CONFIG contains invalid unicode");
+            process::exit(1);
+        }
+    }
+}
+
+/// Prints the integration script for `shell`, with the user's
+/// configuration compiled into it.
+fn init(shell: Shell) {
+    let exe = env::current_exe()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "promptress".into());
+    let path = config_path();
+    let (config, right) = if path.exists() {
+        let mut config = compile_config(&path);
+        config.shell = shell;
+        let right = !config.right_layout.is_empty();
+        (Some(serde_json::to_string(&config).unwrap()), right)
+    } else {
+        (None, false)
+    };
+    match init::script(shell, &exe, config.as_deref(), right) {
+        Some(script) => print!("{}", script),
+        None => {
+            eprintln!("promptress: init does not support this shell");
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut shell = None;
+    let mut right = false;
+    let mut file = None;
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("init") {
+        args.next();
+        match (args.next(), args.next()) {
+            (Some(name), None) => init(parse_shell(&name)),
+            _ => usage(),
+        }
+        return;
+    }
+    while let Some(arg) = args.next() {
+        if arg == "--shell" {
+            match args.next() {
+                Some(name) => shell = Some(parse_shell(&name)),
+                None => usage(),
             }
-            Err(VarError::NotUnicode(_)) => {
-                // Invalid Unicode -- just print the error and exit
-                eprintln!("promptress: PROMPTRESS_CONFIG contains invalid unicode");
-                process::exit(1);
+        } else if arg == "--right" {
+            right = true;
+        } else if file.is_none() && !arg.starts_with('-') {
+            file = Some(arg);
+        } else {
+            usage();
+        }
+    }
+
+    match file {
+        None => {
+            // Display the prompt
+            let mut config = load_config();
+            if let Some(shell) = shell {
+                config.shell = shell;
             }
-        };
-    } else if args.len() == 1 {
-        // Compile a config
-        let s = read_file(&args[0]);
-        let config: Config = match toml::from_str(&s) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("promptress: cannot parse {}: {}", args[0], e);
-                process::exit(1);
+            display_prompt(&config, right);
+        }
+        Some(file) => {
+            // Compile a config
+            let mut config = compile_config(&file);
+            if let Some(shell) = shell {
+                config.shell = shell;
             }
-        };
-        let stdout = io::stdout();
-        serde_json::to_writer(stdout, &config).unwrap();
-    } else {
-        eprintln!("Usage: promptress [FILE]");
-        eprintln!("Without FILE, render the prompt.");
-        eprintln!("With FILE, compile the file configuration.");
-        process::exit(2);
+            let stdout = io::stdout();
+            serde_json::to_writer(stdout, &config).unwrap();
+        }
     }
 }
@@ -1,7 +1,60 @@
-pub use crate::parts::dollar::dollar;
-pub use crate::parts::exitcode::exit_code;
-pub use crate::parts::workdir::work_dir;
+use std::env;
+use std::path::PathBuf;
 
+use crate::{Config, Prompt, SegmentKind};
+
+mod cloud;
+mod command;
 mod dollar;
+mod duration;
+mod envvar;
 mod exitcode;
+mod jobs;
+mod kube;
+mod newline;
+mod pythonenv;
+mod toolchain;
+mod userhost;
 mod workdir;
+
+pub use toolchain::default_markers;
+
+/// A section of the prompt which can be placed in the layout.
+pub trait Segment {
+    /// Writes the segment to the prompt.
+    fn render(&self, p: &mut Prompt);
+}
+
+/// Replaces `{n}` in a format string with a number.
+fn format_count(format: &str, n: u32) -> String {
+    format.replace("{n}", &n.to_string())
+}
+
+/// Returns the working directory, preferring `$PWD` so that symbolic
+/// links are kept.
+fn working_dir() -> PathBuf {
+    env::var_os("PWD")
+        .map(|s| s.into())
+        .unwrap_or_else(|| env::current_dir().expect("cannot get working directory"))
+}
+
+/// Looks up the segment named by `kind` in the configuration.
+pub fn segment<'a>(conf: &'a Config, kind: &SegmentKind) -> &'a dyn Segment {
+    match kind {
+        SegmentKind::ExitCode => &conf.exit_code,
+        SegmentKind::WorkDir => &conf.work_dir,
+        SegmentKind::Dollar => &conf.dollar,
+        SegmentKind::Duration => &conf.duration,
+        SegmentKind::Newline => &conf.newline,
+        SegmentKind::UserHost => &conf.user_host,
+        SegmentKind::Jobs => &conf.jobs,
+        SegmentKind::PythonEnv => &conf.python_env,
+        SegmentKind::Toolchain => &conf.toolchain,
+        SegmentKind::Kube => &conf.kube,
+        SegmentKind::Cloud => &conf.cloud,
+        SegmentKind::Git => &conf.work_dir.git,
+        // The configuration has been validated, so the instance exists
+        SegmentKind::Env(name) => &conf.env[name],
+        SegmentKind::Command(name) => &conf.command[name],
+    }
+}
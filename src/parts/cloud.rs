@@ -0,0 +1,115 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::parts::Segment;
+use crate::{Cloud, Prompt, StyleOverride};
+
+/// Returns the first of the environment variables which is set and not
+/// empty.
+fn first_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Returns the active AWS profile.
+fn aws_profile() -> Option<String> {
+    first_var(&["AWS_PROFILE", "AWS_VAULT", "AWS_DEFAULT_PROFILE"])
+}
+
+/// Returns the active gcloud configuration.
+fn gcp_config() -> Option<String> {
+    if let Some(name) = first_var(&["CLOUDSDK_ACTIVE_CONFIG_NAME"]) {
+        return Some(name);
+    }
+    let dir = match env::var_os("CLOUDSDK_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()?.join(".config").join("gcloud"),
+    };
+    let name = fs::read_to_string(dir.join("active_config")).ok()?;
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.into())
+    }
+}
+
+/// Returns the active Azure subscription.
+fn azure_subscription() -> Option<String> {
+    first_var(&[
+        "AZURE_SUBSCRIPTION_NAME",
+        "AZURE_SUBSCRIPTION",
+        "AZURE_SUBSCRIPTION_ID",
+    ])
+}
+
+impl Cloud {
+    /// Writes one provider's profile, unless it is hidden.
+    fn write_profile(&self, p: &mut Prompt, prefix: &str, profile: &str) {
+        if self.hide_default && profile == self.default_profile {
+            return;
+        }
+        let ov = self.profiles.get(profile);
+        let (bg, sty, label) = StyleOverride::apply(ov, self.bg, self.sty, profile);
+        p.new_part(bg);
+        p.style(sty);
+        write!(p, "{}{}", prefix, label);
+    }
+}
+
+impl Segment for Cloud {
+    fn render(&self, p: &mut Prompt) {
+        if let Some(profile) = aws_profile() {
+            self.write_profile(p, &self.aws_prefix, &profile);
+        }
+        if let Some(config) = gcp_config() {
+            self.write_profile(p, &self.gcp_prefix, &config);
+        }
+        if let Some(subscription) = azure_subscription() {
+            self.write_profile(p, &self.azure_prefix, &subscription);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Config, StyleOverride};
+
+    fn render_profile(conf: &Cloud, profile: &str) -> String {
+        Prompt::render_with(Config::default(), |p| {
+            conf.write_profile(p, "AWS:", profile)
+        })
+    }
+
+    #[test]
+    fn hide_default_profile() {
+        let conf = Cloud::default();
+        assert_eq!(render_profile(&conf, "default"), "");
+        let conf = Cloud {
+            hide_default: false,
+            ..Default::default()
+        };
+        assert_ne!(render_profile(&conf, "default"), "");
+    }
+
+    #[test]
+    fn profile_override() {
+        let mut conf = Cloud::default();
+        conf.profiles.insert(
+            "prod".into(),
+            StyleOverride {
+                label: Some("PROD".into()),
+                bg: Some(Color::Fixed(1)),
+                sty: None,
+            },
+        );
+        assert_eq!(
+            render_profile(&conf, "prod"),
+            "\x1b[0;41m \x1b[0;41;30mAWS:PROD \x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::parts::{working_dir, Segment};
+use crate::{Command, Prompt};
+
+/// Kills a program and everything it started.
+fn kill(child: &mut Child) {
+    // SAFETY: kill() only takes integers. The child leads its own
+    // process group, whose ID is not reused until every process in it
+    // has exited, so no other process can be signalled.
+    let ret = unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+    if ret != 0 {
+        // The group cannot be signalled, so at least stop the child.
+        // This fails only if it has already exited.
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+/// Runs a program and returns the first line of its output. Returns
+/// `None` if it cannot be run, fails, or does not finish in time.
+fn run(command: &[String], timeout: Duration) -> Option<String> {
+    let deadline = Instant::now() + timeout;
+    let (program, args) = command.split_first()?;
+    let mut child = process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        let _ = tx.send(output);
+    });
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(1)),
+            _ => {
+                kill(&mut child);
+                return None;
+            }
+        }
+    };
+    // Something the program started may still be writing its output
+    match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(output) if status.success() => Some(output.lines().next().unwrap_or("").trim().into()),
+        Ok(_) => None,
+        Err(_) => {
+            kill(&mut child);
+            None
+        }
+    }
+}
+
+/// Returns a string which changes whenever the modification time of
+/// one of `paths` does.
+fn stamp(paths: &[&Path]) -> Option<String> {
+    let mut stamp = String::new();
+    for path in paths {
+        let mtime = fs::metadata(path).ok()?.modified().ok()?;
+        let mtime = mtime.duration_since(UNIX_EPOCH).ok()?;
+        stamp += &format!("{}.{:09} ", mtime.as_secs(), mtime.subsec_nanos());
+    }
+    Some(stamp)
+}
+
+/// Returns the file caching the output of `command` in `dir`.
+fn cache_path(runtime_dir: &Path, command: &[String], dir: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    command.hash(&mut hasher);
+    dir.hash(&mut hasher);
+    runtime_dir
+        .join("promptress")
+        .join(format!("{:016x}", hasher.finish()))
+}
+
+/// Reads a cached output, if it was saved with the same stamp.
+fn read_cache(path: &Path, stamp: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    let (saved, output) = contents.split_once('\n')?;
+    if saved == stamp {
+        Some(output.into())
+    } else {
+        None
+    }
+}
+
+fn write_cache(path: &Path, stamp: &str, output: &str) {
+    // The cache is only an optimization, so errors are ignored
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, format!("{}\n{}", stamp, output));
+}
+
+impl Command {
+    /// Returns the output of the command in `dir`, from the cache if
+    /// neither `dir` nor `marker` have changed since it was saved.
+    fn output(&self, dir: &Path, marker: Option<&Path>) -> Option<String> {
+        let timeout = Duration::from_millis(self.timeout_ms);
+        let runtime_dir = match env::var_os("XDG_RUNTIME_DIR") {
+            Some(d) if self.cache && !d.is_empty() => PathBuf::from(d),
+            _ => return run(&self.command, timeout),
+        };
+        let mut paths = vec![dir];
+        paths.extend(marker);
+        let stamp = match stamp(&paths) {
+            Some(s) => s,
+            None => return run(&self.command, timeout),
+        };
+        let path = cache_path(&runtime_dir, &self.command, dir);
+        if let Some(output) = read_cache(&path, &stamp) {
+            return Some(output);
+        }
+        let output = run(&self.command, timeout)?;
+        write_cache(&path, &stamp, &output);
+        Some(output)
+    }
+}
+
+impl Segment for Command {
+    fn render(&self, p: &mut Prompt) {
+        let dir = working_dir();
+        let marker = if self.if_exists.is_empty() {
+            None
+        } else {
+            match dir
+                .ancestors()
+                .map(|d| d.join(&self.if_exists))
+                .find(|m| m.exists())
+            {
+                Some(m) => Some(m),
+                None => return,
+            }
+        };
+        let output = match self.output(&dir, marker.as_deref()) {
+            Some(o) if !o.is_empty() => o,
+            _ => return,
+        };
+        p.new_part(self.bg);
+        p.style(self.sty);
+        write!(p, "{}", output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".into(), "-c".into(), script.into()]
+    }
+
+    #[test]
+    fn run_first_line() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            run(&sh("echo '  main  '; echo more"), timeout).as_deref(),
+            Some("main")
+        );
+        assert_eq!(run(&sh("true"), timeout).as_deref(), Some(""));
+        assert_eq!(run(&sh("echo oops; exit 1"), timeout), None);
+        assert_eq!(run(&[], timeout), None);
+    }
+
+    #[test]
+    fn run_timeout() {
+        let timeout = Duration::from_millis(50);
+        let start = Instant::now();
+        assert_eq!(run(&sh("sleep 5"), timeout), None);
+        // Closing stdout early does not escape the timeout
+        assert_eq!(run(&sh("exec >&-; sleep 5"), timeout), None);
+        // Neither does a background process holding stdout open
+        assert_eq!(run(&sh("sleep 5 & echo hi"), timeout), None);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn cache() {
+        let dir = env::temp_dir().join(format!("promptress-test-{}", process::id()));
+        let path = cache_path(&dir, &sh("echo hi"), Path::new("/"));
+        assert_ne!(path, cache_path(&dir, &sh("echo hi"), Path::new("/tmp")));
+        assert_eq!(read_cache(&path, "1.0 "), None);
+        write_cache(&path, "1.0 ", "hi");
+        assert_eq!(read_cache(&path, "1.0 ").as_deref(), Some("hi"));
+        assert_eq!(read_cache(&path, "2.0 "), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,16 +1,19 @@
-use crate::Prompt;
+use crate::parts::Segment;
+use crate::{Dollar, Prompt};
 
 fn is_root() -> bool {
     uzers::get_effective_uid() == 0
 }
 
-pub fn dollar(p: &mut Prompt) {
-    p.new_part(p.conf.dollar.bg);
-    if is_root() {
-        p.style(p.conf.dollar.root_sty);
-        print!("#");
-    } else {
-        p.style(p.conf.dollar.user_sty);
-        print!("$");
+impl Segment for Dollar {
+    fn render(&self, p: &mut Prompt) {
+        p.new_part(self.bg);
+        if is_root() {
+            p.style(self.root_sty);
+            write!(p, "#");
+        } else {
+            p.style(self.user_sty);
+            write!(p, "$");
+        }
     }
 }
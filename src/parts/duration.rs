@@ -0,0 +1,67 @@
+use std::env;
+
+use crate::parts::Segment;
+use crate::{Duration, Prompt};
+
+/// Formats a duration in a human-friendly way, e.g. `450ms`, `12s`,
+/// `1m23s` or `2h0m5s`.
+fn format_duration(ms: u64) -> String {
+    if ms < 1000 {
+        return format!("{}ms", ms);
+    }
+    let secs = ms / 1000;
+    let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h{}m{}s", hours, mins, secs)
+    } else if mins > 0 {
+        format!("{}m{}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl Segment for Duration {
+    fn render(&self, p: &mut Prompt) {
+        let ms: u64 = match env::var("PROMPTRESS_DURATION_MS") {
+            Ok(ms) => match ms.parse() {
+                Ok(ms) => ms,
+                Err(_) => return,
+            },
+            Err(_) => return,
+        };
+        if ms < self.min_ms {
+            return;
+        }
+        p.new_part(self.bg);
+        p.style(self.sty);
+        write!(p, "{}", format_duration(ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_millis() {
+        assert_eq!(format_duration(0), "0ms");
+        assert_eq!(format_duration(450), "450ms");
+    }
+
+    #[test]
+    fn format_seconds() {
+        assert_eq!(format_duration(1000), "1s");
+        assert_eq!(format_duration(59_999), "59s");
+    }
+
+    #[test]
+    fn format_minutes() {
+        assert_eq!(format_duration(83_000), "1m23s");
+        assert_eq!(format_duration(3_599_000), "59m59s");
+    }
+
+    #[test]
+    fn format_hours() {
+        assert_eq!(format_duration(7_205_000), "2h0m5s");
+    }
+}
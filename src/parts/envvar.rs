@@ -0,0 +1,76 @@
+use std::env;
+
+use crate::parts::Segment;
+use crate::{Env, Prompt, StyleOverride};
+
+impl Env {
+    /// Writes the segment, given the value of the variable.
+    fn write(&self, p: &mut Prompt, value: Option<&str>) {
+        let value = value.unwrap_or("");
+        if value.is_empty() && self.hide_if_unset {
+            return;
+        }
+        let ov = self.values.get(value);
+        let (bg, sty, label) = StyleOverride::apply(ov, self.bg, self.sty, value);
+        p.new_part(bg);
+        p.style(sty);
+        write!(p, "{}", self.format.replace("{value}", label));
+    }
+}
+
+impl Segment for Env {
+    fn render(&self, p: &mut Prompt) {
+        let value = env::var_os(&self.var).map(|v| v.to_string_lossy().into_owned());
+        self.write(p, value.as_deref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Config, StyleOverride};
+
+    fn render_value(conf: &Env, value: Option<&str>) -> String {
+        Prompt::render_with(Config::default(), |p| conf.write(p, value))
+    }
+
+    #[test]
+    fn hide_if_unset() {
+        let conf = Env::default();
+        assert_eq!(render_value(&conf, None), "");
+        assert_eq!(render_value(&conf, Some("")), "");
+        let conf = Env {
+            hide_if_unset: false,
+            format: "[{value}]".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_value(&conf, None),
+            "\x1b[0;40m \x1b[0;40;97m[] \x1b[0m"
+        );
+    }
+
+    #[test]
+    fn format_and_override() {
+        let mut conf = Env {
+            format: "region={value}".into(),
+            ..Default::default()
+        };
+        conf.values.insert(
+            "us-east-1".into(),
+            StyleOverride {
+                label: Some("virginia".into()),
+                bg: Some(Color::Fixed(1)),
+                sty: None,
+            },
+        );
+        assert_eq!(
+            render_value(&conf, Some("eu-west-1")),
+            "\x1b[0;40m \x1b[0;40;97mregion=eu-west-1 \x1b[0m"
+        );
+        assert_eq!(
+            render_value(&conf, Some("us-east-1")),
+            "\x1b[0;41m \x1b[0;41;97mregion=virginia \x1b[0m"
+        );
+    }
+}
@@ -1,20 +1,219 @@
 use std::borrow::Cow;
 use std::env;
 
-use crate::Prompt;
-
-pub fn exit_code(p: &mut Prompt) {
-    let code: Cow<str> = match env::var("PROMPTRESS_EXIT_CODE") {
-        Ok(c) => c.into(),
-        Err(_) => "?".into(),
-    };
-    if code == "0" {
-        p.new_part(p.conf.exit_code.success_bg);
-        p.style(p.conf.exit_code.success_sty);
-        print!("{}", code);
+use smallvec::SmallVec;
+
+use crate::parts::Segment;
+use crate::{ExitCode, ExitCodeDisplay, Prompt};
+
+/// Names of signals 1 to 31, without the `SIG` prefix.
+#[cfg(target_os = "linux")]
+const SIGNALS: [&str; 31] = [
+    "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "BUS", "FPE", "KILL", "USR1", "SEGV", "USR2",
+    "PIPE", "ALRM", "TERM", "STKFLT", "CHLD", "CONT", "STOP", "TSTP", "TTIN", "TTOU", "URG",
+    "XCPU", "XFSZ", "VTALRM", "PROF", "WINCH", "IO", "PWR", "SYS",
+];
+
+/// Names of signals 1 to 31, without the `SIG` prefix.
+#[cfg(not(target_os = "linux"))]
+const SIGNALS: [&str; 31] = [
+    "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "EMT", "FPE", "KILL", "BUS", "SEGV", "SYS",
+    "PIPE", "ALRM", "TERM", "URG", "STOP", "TSTP", "CONT", "CHLD", "TTIN", "TTOU", "IO", "XCPU",
+    "XFSZ", "VTALRM", "PROF", "WINCH", "INFO", "USR1", "USR2",
+];
+
+/// Returns the name of the signal which killed a process, given its
+/// exit code as reported by the shell.
+fn signal_name(code: u32) -> Option<&'static str> {
+    let signal = code.checked_sub(128)?;
+    SIGNALS.get(signal.checked_sub(1)? as usize).copied()
+}
+
+impl ExitCode {
+    /// Returns the label for an exit code, if it has one: either one
+    /// configured by the user, or the name of a signal.
+    fn label(&self, code: &str) -> Option<&str> {
+        if let Some(label) = self.labels.get(code) {
+            return Some(label);
+        }
+        code.parse().ok().and_then(signal_name)
+    }
+
+    /// Returns the text to display for an exit code.
+    fn describe<'a>(&'a self, code: &'a str) -> Cow<'a, str> {
+        let success = code == "0";
+        match self.display {
+            ExitCodeDisplay::Code => code.into(),
+            ExitCodeDisplay::Name => self.label(code).unwrap_or(code).into(),
+            ExitCodeDisplay::Both => match self.label(code) {
+                Some(label) => format!("{} {}", code, label).into(),
+                None => code.into(),
+            },
+            ExitCodeDisplay::Icon if success => self.success_icon.as_str().into(),
+            ExitCodeDisplay::Icon => self.failure_icon.as_str().into(),
+        }
+    }
+}
+
+/// Splits the exit codes of a pipeline. Returns an empty list if the
+/// pipeline should be collapsed into a single code, because it has
+/// only one command or all of its codes are equal.
+fn split_pipestatus(pipestatus: &str) -> SmallVec<[&str; 8]> {
+    let codes: SmallVec<[&str; 8]> = pipestatus.split_whitespace().collect();
+    if codes.iter().all(|&c| c == codes[0]) {
+        SmallVec::new()
     } else {
-        p.new_part(p.conf.exit_code.failure_bg);
-        p.style(p.conf.exit_code.failure_sty);
-        print!("{}", code);
+        codes
+    }
+}
+
+impl ExitCode {
+    /// Writes the exit code, given the values of `PROMPTRESS_EXIT_CODE`
+    /// and `PROMPTRESS_PIPESTATUS`.
+    pub fn write(&self, p: &mut Prompt, code: Option<&str>, pipestatus: &str) {
+        let codes = split_pipestatus(pipestatus);
+        let code = code
+            .or_else(|| pipestatus.split_whitespace().last())
+            .unwrap_or("?");
+        // A pipeline with a failed command is shown even on success
+        let success = code == "0";
+        if success && codes.is_empty() && !self.show_on_success {
+            return;
+        }
+        let sty = if success {
+            p.new_part(self.success_bg);
+            self.success_sty
+        } else {
+            p.new_part(self.failure_bg);
+            self.failure_sty
+        };
+        if codes.is_empty() {
+            p.style(sty);
+            if success && self.icon_on_success {
+                write!(p, "{}", self.success_icon);
+            } else {
+                write!(p, "{}", self.describe(code));
+            }
+            return;
+        }
+        for (i, &c) in codes.iter().enumerate() {
+            if i > 0 {
+                p.style(sty);
+                write!(p, "{}", self.pipe_separator);
+            }
+            if c == "0" {
+                p.style(self.success_sty);
+            } else {
+                p.style(self.failure_sty);
+            }
+            write!(p, "{}", self.describe(c));
+        }
+    }
+}
+
+impl Segment for ExitCode {
+    fn render(&self, p: &mut Prompt) {
+        let code = env::var("PROMPTRESS_EXIT_CODE").ok();
+        let pipestatus = env::var("PROMPTRESS_PIPESTATUS").unwrap_or_default();
+        self.write(p, code.as_deref(), &pipestatus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Config};
+
+    fn exit_code(display: ExitCodeDisplay) -> ExitCode {
+        let mut conf = ExitCode {
+            display,
+            ..Default::default()
+        };
+        conf.labels.insert("127".into(), "not found".into());
+        conf
+    }
+
+    #[test]
+    fn signal_names() {
+        assert_eq!(signal_name(1), None);
+        assert_eq!(signal_name(128), None);
+        assert_eq!(signal_name(130), Some("INT"));
+        assert_eq!(signal_name(137), Some("KILL"));
+        assert_eq!(signal_name(139), Some("SEGV"));
+        assert_eq!(signal_name(200), None);
+    }
+
+    #[test]
+    fn describe_code() {
+        let conf = exit_code(ExitCodeDisplay::Code);
+        assert_eq!(conf.describe("130"), "130");
+        assert_eq!(conf.describe("127"), "127");
+    }
+
+    #[test]
+    fn describe_name() {
+        let conf = exit_code(ExitCodeDisplay::Name);
+        assert_eq!(conf.describe("0"), "0");
+        assert_eq!(conf.describe("130"), "INT");
+        assert_eq!(conf.describe("127"), "not found");
+        assert_eq!(conf.describe("?"), "?");
+    }
+
+    #[test]
+    fn describe_both() {
+        let conf = exit_code(ExitCodeDisplay::Both);
+        assert_eq!(conf.describe("1"), "1");
+        assert_eq!(conf.describe("139"), "139 SEGV");
+        assert_eq!(conf.describe("127"), "127 not found");
+    }
+
+    #[test]
+    fn describe_icon() {
+        let conf = exit_code(ExitCodeDisplay::Icon);
+        assert_eq!(conf.describe("0"), conf.success_icon);
+        assert_eq!(conf.describe("130"), conf.failure_icon);
+    }
+
+    #[test]
+    fn pipestatus_collapses() {
+        assert!(split_pipestatus("").is_empty());
+        assert!(split_pipestatus("1").is_empty());
+        assert!(split_pipestatus("0 0 0").is_empty());
+    }
+
+    #[test]
+    fn pipestatus_mixed() {
+        assert_eq!(split_pipestatus("0 1 0").as_slice(), ["0", "1", "0"]);
+        assert_eq!(split_pipestatus(" 141  0 ").as_slice(), ["141", "0"]);
+    }
+
+    /// Renders an exit code followed by another part.
+    fn render_before_part(conf: &ExitCode, code: &str, pipestatus: &str) -> String {
+        Prompt::render_with(Config::default(), |p| {
+            conf.write(p, Some(code), pipestatus);
+            p.new_part(Color::Fixed(1));
+        })
+    }
+
+    #[test]
+    fn hidden_on_success() {
+        let conf = ExitCode {
+            show_on_success: false,
+            ..Default::default()
+        };
+        assert_eq!(render_before_part(&conf, "0", ""), "\x1b[0;41m  \x1b[0m");
+        assert_ne!(render_before_part(&conf, "0", "1 0"), "\x1b[0;41m  \x1b[0m");
+    }
+
+    #[test]
+    fn icon_on_success() {
+        let conf = ExitCode {
+            icon_on_success: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            render_before_part(&conf, "0", ""),
+            "\x1b[0;40m \x1b[0;40;92;1m\u{2714} \x1b[0;30;41m\u{e0b0}\x1b[0;41m  \x1b[0m"
+        );
     }
 }
@@ -0,0 +1,63 @@
+use std::env;
+
+use crate::parts::{format_count, Segment};
+use crate::{Jobs, Prompt};
+
+/// Parses the value of `PROMPTRESS_JOBS`. Anything that is not a
+/// number counts as no jobs.
+fn parse_jobs(value: &str) -> u32 {
+    value.trim().parse().unwrap_or(0)
+}
+
+impl Jobs {
+    /// Returns the indicator for a number of jobs, or `None` if there
+    /// are none.
+    fn indicator(&self, jobs: u32) -> Option<String> {
+        match jobs {
+            0 => None,
+            1 => Some(format_count(&self.one, jobs)),
+            _ => Some(format_count(&self.many, jobs)),
+        }
+    }
+}
+
+impl Segment for Jobs {
+    fn render(&self, p: &mut Prompt) {
+        let jobs = env::var("PROMPTRESS_JOBS")
+            .map(|n| parse_jobs(&n))
+            .unwrap_or(0);
+        let indicator = match self.indicator(jobs) {
+            Some(i) => i,
+            None => return,
+        };
+        p.new_part(self.bg);
+        p.style(self.sty);
+        write!(p, "{}", indicator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_jobs("3"), 3);
+        assert_eq!(parse_jobs(" 2\n"), 2);
+        assert_eq!(parse_jobs(""), 0);
+        assert_eq!(parse_jobs("two"), 0);
+        assert_eq!(parse_jobs("-1"), 0);
+    }
+
+    #[test]
+    fn indicators() {
+        let conf = Jobs {
+            one: "job".into(),
+            many: "{n} jobs".into(),
+            ..Default::default()
+        };
+        assert_eq!(conf.indicator(0), None);
+        assert_eq!(conf.indicator(1).as_deref(), Some("job"));
+        assert_eq!(conf.indicator(12).as_deref(), Some("12 jobs"));
+    }
+}
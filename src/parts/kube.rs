@@ -0,0 +1,223 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::parts::Segment;
+use crate::{Kube, Prompt, StyleOverride};
+
+/// The parts of a kubeconfig file which are displayed.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct KubeConfig {
+    current_context: Option<String>,
+    /// Names and namespaces of contexts.
+    contexts: Vec<(String, Option<String>)>,
+}
+
+/// Removes quotes or a trailing comment from a YAML scalar.
+fn yaml_scalar(value: &str) -> &str {
+    let value = value.trim();
+    for &quote in &['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            if let Some(end) = rest.find(quote) {
+                return &rest[..end];
+            }
+        }
+    }
+    match value.find(" #") {
+        Some(i) => value[..i].trim_end(),
+        None => value,
+    }
+}
+
+/// Splits a YAML line into a key and value. Values may contain colons,
+/// e.g. EKS context names.
+fn yaml_key_value(line: &str) -> Option<(&str, &str)> {
+    if let Some(i) = line.find(": ") {
+        Some((&line[..i], yaml_scalar(&line[i + 2..])))
+    } else {
+        line.strip_suffix(':').map(|key| (key, ""))
+    }
+}
+
+/// Parses the subset of YAML used by kubeconfig files written by
+/// `kubectl` and by hand, without a full YAML parser.
+fn parse_yaml(s: &str) -> KubeConfig {
+    let mut conf = KubeConfig::default();
+    let mut section = "";
+    // Indentation of the keys in the current list item
+    let mut item_indent = 0;
+    for line in s.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if indent == 0 && !trimmed.starts_with('-') {
+            if let Some((key, value)) = yaml_key_value(trimmed) {
+                section = key;
+                if key == "current-context" && !value.is_empty() {
+                    conf.current_context = Some(value.into());
+                }
+            }
+            continue;
+        }
+        if section != "contexts" {
+            continue;
+        }
+        let entry = match trimmed.strip_prefix("- ") {
+            Some(rest) => {
+                conf.contexts.push(Default::default());
+                item_indent = indent + 2;
+                rest
+            }
+            None => trimmed,
+        };
+        let context = match conf.contexts.last_mut() {
+            Some(c) => c,
+            None => continue,
+        };
+        match yaml_key_value(entry) {
+            Some(("name", value)) if indent <= item_indent => context.0 = value.into(),
+            Some(("namespace", value)) if indent > item_indent => {
+                context.1 = Some(value.into());
+            }
+            _ => (),
+        }
+    }
+    conf
+}
+
+/// Parses a kubeconfig file in JSON.
+fn parse_json(s: &str) -> KubeConfig {
+    let json: serde_json::Value = match serde_json::from_str(s) {
+        Ok(json) => json,
+        Err(_) => return Default::default(),
+    };
+    let str_at = |value: &serde_json::Value, pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+    let contexts = match json.get("contexts").and_then(|c| c.as_array()) {
+        Some(contexts) => contexts
+            .iter()
+            .filter_map(|c| Some((str_at(c, "/name")?, str_at(c, "/context/namespace"))))
+            .collect(),
+        None => vec![],
+    };
+    KubeConfig {
+        current_context: str_at(&json, "/current-context"),
+        contexts,
+    }
+}
+
+fn parse_kubeconfig(s: &str) -> KubeConfig {
+    if s.trim_start().starts_with('{') {
+        parse_json(s)
+    } else {
+        parse_yaml(s)
+    }
+}
+
+/// Returns the paths of the kubeconfig files, from `$KUBECONFIG` or
+/// the default location.
+fn kubeconfig_paths() -> Vec<PathBuf> {
+    match env::var_os("KUBECONFIG") {
+        Some(paths) => env::split_paths(&paths).collect(),
+        None => dirs::home_dir()
+            .map(|home| vec![home.join(".kube").join("config")])
+            .unwrap_or_default(),
+    }
+}
+
+/// Merges the kubeconfig files in the same way as `kubectl`: the first
+/// value set for each setting wins. Returns the current context and
+/// its namespace.
+fn current_context(files: &[KubeConfig]) -> Option<(&str, Option<&str>)> {
+    let context = files.iter().find_map(|f| f.current_context.as_deref())?;
+    let namespace = files
+        .iter()
+        .flat_map(|f| &f.contexts)
+        .find(|(name, _)| name == context)
+        .and_then(|(_, ns)| ns.as_deref());
+    Some((context, namespace))
+}
+
+impl Segment for Kube {
+    fn render(&self, p: &mut Prompt) {
+        let files: Vec<KubeConfig> = kubeconfig_paths()
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .map(|s| parse_kubeconfig(&s))
+            .collect();
+        let (context, namespace) = match current_context(&files) {
+            Some(c) => c,
+            None => return,
+        };
+        let ov = self.contexts.get(context);
+        let (bg, sty, label) = StyleOverride::apply(ov, self.bg, self.sty, context);
+        p.new_part(bg);
+        p.style(sty);
+        write!(p, "{}{}", self.prefix, label);
+        if let Some(namespace) = namespace.filter(|_| self.show_namespace) {
+            write!(p, "{}{}", self.separator, namespace);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> KubeConfig {
+        let path = format!(
+            "{}/tests/fixtures/kube/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        parse_kubeconfig(&fs::read_to_string(path).unwrap())
+    }
+
+    #[test]
+    fn kubectl_format() {
+        let conf = fixture("kubectl.yaml");
+        let eks = "arn:aws:eks:us-east-1:123456789012:cluster/prod";
+        assert_eq!(
+            conf,
+            KubeConfig {
+                current_context: Some(eks.into()),
+                contexts: vec![
+                    ("kind-kind".into(), None),
+                    (eks.into(), Some("payments".into())),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn indented_and_quoted() {
+        let conf = fixture("indented.yaml");
+        assert_eq!(
+            conf,
+            KubeConfig {
+                current_context: Some("staging".into()),
+                contexts: vec![("dev".into(), None), ("staging".into(), Some("web".into())),],
+            }
+        );
+    }
+
+    #[test]
+    fn json_format() {
+        let conf = fixture("json.json");
+        assert_eq!(current_context(&[conf]), Some(("dev", Some("sandbox"))));
+    }
+
+    #[test]
+    fn merge_files() {
+        let files = [fixture("no-current.yaml"), fixture("indented.yaml")];
+        assert_eq!(current_context(&files), Some(("staging", Some("web"))));
+        let files = [fixture("no-current.yaml")];
+        assert_eq!(current_context(&files), None);
+    }
+}
@@ -0,0 +1,8 @@
+use crate::parts::Segment;
+use crate::{Newline, Prompt};
+
+impl Segment for Newline {
+    fn render(&self, p: &mut Prompt) {
+        p.newline();
+    }
+}
@@ -0,0 +1,125 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::parts::Segment;
+use crate::{Prompt, PythonEnv};
+
+/// Returns the name of a virtualenv given its path. A virtualenv named
+/// `.venv` is named after its parent directory instead.
+fn venv_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?;
+    let name = if name == ".venv" {
+        path.parent()?.file_name()?
+    } else {
+        name
+    };
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Reads the Python version from the contents of `pyvenv.cfg`.
+fn pyvenv_version(cfg: &str) -> Option<&str> {
+    for line in cfg.lines() {
+        let (key, value) = match line.split_once('=') {
+            Some(kv) => kv,
+            None => continue,
+        };
+        match key.trim() {
+            "version" => return Some(value.trim()),
+            "version_info" => {
+                // e.g. 3.12.1.final.0
+                let value = value.trim();
+                let end = value
+                    .match_indices('.')
+                    .nth(2)
+                    .map_or(value.len(), |(i, _)| i);
+                return Some(&value[..end]);
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Finds the Python version in a conda environment, from the name of
+/// its package metadata, e.g. `conda-meta/python-3.11.4-h955ad1f_0.json`.
+fn conda_version(prefix: &Path) -> Option<String> {
+    for ent in fs::read_dir(prefix.join("conda-meta")).ok()? {
+        let name = ent.ok()?.file_name();
+        let name = name.to_string_lossy();
+        if let Some(rest) = name.strip_prefix("python-") {
+            if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                return rest.split('-').next().map(String::from);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the name and Python version of the active environment.
+fn active_env(version: bool) -> Option<(String, Option<String>)> {
+    if let Some(path) = env::var_os("VIRTUAL_ENV") {
+        let path = PathBuf::from(path);
+        let ver = if version {
+            fs::read_to_string(path.join("pyvenv.cfg"))
+                .ok()
+                .and_then(|cfg| pyvenv_version(&cfg).map(String::from))
+        } else {
+            None
+        };
+        return Some((venv_name(&path)?, ver));
+    }
+    let prefix = env::var_os("CONDA_PREFIX").map(PathBuf::from);
+    let name = match env::var("CONDA_DEFAULT_ENV") {
+        Ok(name) => name,
+        Err(_) => prefix.as_ref()?.file_name()?.to_string_lossy().into_owned(),
+    };
+    let ver = match prefix {
+        Some(prefix) if version => conda_version(&prefix),
+        _ => None,
+    };
+    Some((name, ver))
+}
+
+impl Segment for PythonEnv {
+    fn render(&self, p: &mut Prompt) {
+        let (name, version) = match active_env(self.show_version) {
+            Some(env) => env,
+            None => return,
+        };
+        p.new_part(self.bg);
+        p.style(self.sty);
+        write!(p, "{}{}", self.prefix, name);
+        if let Some(version) = version {
+            write!(p, " {}", version);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn venv_names() {
+        assert_eq!(venv_name(Path::new("/home/u/envs/foo")).unwrap(), "foo");
+        assert_eq!(venv_name(Path::new("/home/u/proj/.venv")).unwrap(), "proj");
+    }
+
+    #[test]
+    fn pyvenv_cfg_version() {
+        let cfg = "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.11.4\n";
+        assert_eq!(pyvenv_version(cfg), Some("3.11.4"));
+    }
+
+    #[test]
+    fn pyvenv_cfg_version_info() {
+        let cfg = "home = /usr/bin\nimplementation = CPython\nversion_info = 3.12.1.final.0\n";
+        assert_eq!(pyvenv_version(cfg), Some("3.12.1"));
+    }
+
+    #[test]
+    fn pyvenv_cfg_no_version() {
+        assert_eq!(pyvenv_version("home = /usr/bin\n"), None);
+    }
+}
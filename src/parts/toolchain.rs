@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::parts::{working_dir, Segment};
+use crate::{Prompt, Toolchain};
+
+/// Reads the pinned version from the contents of a marker file.
+type VersionReader = fn(&str) -> Option<String>;
+
+/// Known marker files, with their default icons and how to read their
+/// versions, in order of precedence.
+const KNOWN_MARKERS: &[(&str, &str, VersionReader)] = &[
+    ("rust-toolchain.toml", "Rust", rust_toolchain),
+    ("rust-toolchain", "Rust", rust_toolchain),
+    ("Cargo.toml", "Rust", cargo_toml),
+    (".nvmrc", "Node", node_version),
+    (".node-version", "Node", node_version),
+    ("package.json", "Node", package_json),
+    ("go.mod", "Go", go_mod),
+    (".python-version", "Python", first_line),
+    ("pyproject.toml", "Python", pyproject_toml),
+];
+
+/// Returns the default icon for each known marker file.
+pub fn default_markers() -> HashMap<String, String> {
+    KNOWN_MARKERS
+        .iter()
+        .map(|&(file, icon, _)| (file.into(), icon.into()))
+        .collect()
+}
+
+/// Looks up a string in a TOML document by its path.
+fn toml_str(s: &str, path: &[&str]) -> Option<String> {
+    let mut value = toml::Value::Table(s.parse().ok()?);
+    for key in path {
+        value = value.as_table_mut()?.remove(*key)?;
+    }
+    value.as_str().map(String::from)
+}
+
+fn first_line(s: &str) -> Option<String> {
+    let line = s.lines().next()?.trim();
+    if line.is_empty() {
+        None
+    } else {
+        Some(line.into())
+    }
+}
+
+fn rust_toolchain(s: &str) -> Option<String> {
+    // `rust-toolchain` may be in TOML or just contain the channel
+    toml_str(s, &["toolchain", "channel"]).or_else(|| first_line(s).filter(|l| !l.starts_with('[')))
+}
+
+fn cargo_toml(s: &str) -> Option<String> {
+    toml_str(s, &["package", "rust-version"])
+}
+
+fn node_version(s: &str) -> Option<String> {
+    first_line(s).map(|v| v.trim_start_matches('v').into())
+}
+
+fn package_json(s: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(s).ok()?;
+    json.get("engines")?.get("node")?.as_str().map(String::from)
+}
+
+fn go_mod(s: &str) -> Option<String> {
+    // A `toolchain` line is more specific than the `go` line
+    let mut version = None;
+    for line in s.lines() {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("toolchain"), Some(v)) => return Some(v.trim_start_matches("go").into()),
+            (Some("go"), Some(v)) => version = Some(v.into()),
+            _ => (),
+        }
+    }
+    version
+}
+
+fn pyproject_toml(s: &str) -> Option<String> {
+    toml_str(s, &["project", "requires-python"])
+}
+
+/// Reads the version pinned by a marker file, if it is known.
+fn read_version(dir: &Path, marker: &str) -> Option<String> {
+    let (_, _, reader) = KNOWN_MARKERS.iter().find(|(name, _, _)| *name == marker)?;
+    let contents = fs::read_to_string(dir.join(marker)).ok()?;
+    reader(&contents)
+}
+
+/// Finds the nearest directory, starting from `dir`, which contains
+/// any of the marker files. Returns the directory and the markers
+/// found in it, in order of precedence.
+fn find_markers<'a>(
+    dir: &'a Path,
+    markers: &'a HashMap<String, String>,
+) -> Option<(&'a Path, Vec<&'a str>)> {
+    let precedence = |marker: &str| {
+        KNOWN_MARKERS
+            .iter()
+            .position(|(name, _, _)| *name == marker)
+            .unwrap_or(KNOWN_MARKERS.len())
+    };
+    dir.ancestors().find_map(|dir| {
+        let mut found: Vec<&str> = markers
+            .keys()
+            .map(String::as_str)
+            .filter(|marker| dir.join(marker).is_file())
+            .collect();
+        if found.is_empty() {
+            return None;
+        }
+        found.sort_by_key(|&marker| (precedence(marker), marker));
+        Some((dir, found))
+    })
+}
+
+impl Segment for Toolchain {
+    fn render(&self, p: &mut Prompt) {
+        let dir = working_dir();
+        let (dir, found) = match find_markers(&dir, &self.markers) {
+            Some(f) => f,
+            None => return,
+        };
+        // Markers with the same icon are shown once, with the first
+        // version found
+        let mut toolchains: Vec<(&str, Option<String>)> = vec![];
+        for marker in found {
+            let icon = self.markers[marker].as_str();
+            let index = match toolchains.iter().position(|&(i, _)| i == icon) {
+                Some(index) => index,
+                None => {
+                    toolchains.push((icon, None));
+                    toolchains.len() - 1
+                }
+            };
+            let version = &mut toolchains[index].1;
+            if self.show_version && version.is_none() {
+                *version = read_version(dir, marker);
+            }
+        }
+        for (icon, version) in toolchains {
+            p.new_part(self.bg);
+            p.style(self.sty);
+            write!(p, "{}", icon);
+            if let Some(version) = version {
+                write!(p, " {}", version);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_toolchain_file() {
+        assert_eq!(
+            rust_toolchain("[toolchain]\nchannel = \"1.75.0\"\n").unwrap(),
+            "1.75.0"
+        );
+        assert_eq!(
+            rust_toolchain("nightly-2024-01-01\n").unwrap(),
+            "nightly-2024-01-01"
+        );
+        assert_eq!(rust_toolchain("[toolchain]\nprofile = \"minimal\"\n"), None);
+    }
+
+    #[test]
+    fn cargo_toml_rust_version() {
+        let s = "[package]\nname = \"foo\"\nrust-version = \"1.70\"\n";
+        assert_eq!(cargo_toml(s).unwrap(), "1.70");
+        assert_eq!(cargo_toml("[package]\nname = \"foo\"\n"), None);
+    }
+
+    #[test]
+    fn node_versions() {
+        assert_eq!(node_version("v18.17.0\n").unwrap(), "18.17.0");
+        assert_eq!(
+            package_json(r#"{"name": "foo", "engines": {"node": ">=18"}}"#).unwrap(),
+            ">=18"
+        );
+        assert_eq!(package_json(r#"{"name": "foo"}"#), None);
+    }
+
+    #[test]
+    fn go_mod_version() {
+        assert_eq!(go_mod("module foo\n\ngo 1.21\n").unwrap(), "1.21");
+        assert_eq!(
+            go_mod("module foo\n\ngo 1.21\n\ntoolchain go1.21.3\n").unwrap(),
+            "1.21.3"
+        );
+    }
+
+    #[test]
+    fn pyproject_requires_python() {
+        let s = "[project]\nname = \"foo\"\nrequires-python = \">=3.10\"\n";
+        assert_eq!(pyproject_toml(s).unwrap(), ">=3.10");
+    }
+}
@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+use std::env;
+use std::ffi::CStr;
+
+use crate::parts::Segment;
+use crate::{Prompt, UserHost};
+
+/// Returns the host name of this machine.
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, and the last
+    // byte is never written, so the name is always null-terminated
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len() - 1) };
+    if ret != 0 {
+        return None;
+    }
+    let name = CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Returns whether we are inside an SSH session.
+fn is_ssh() -> bool {
+    env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+}
+
+/// Shortens a host name to its first component.
+fn short_hostname(host: &str) -> &str {
+    host.split('.').next().unwrap_or(host)
+}
+
+impl UserHost {
+    /// Returns whether the segment should be displayed.
+    fn should_show(&self, user: &str, ssh: bool) -> bool {
+        self.show_always || ssh || user != self.default_user
+    }
+}
+
+impl Segment for UserHost {
+    fn render(&self, p: &mut Prompt) {
+        let user: Cow<str> = match uzers::get_effective_username() {
+            Some(name) => name.to_string_lossy().into_owned().into(),
+            None => "?".into(),
+        };
+        let ssh = is_ssh();
+        if !self.should_show(&user, ssh) {
+            return;
+        }
+        let host = hostname().unwrap_or_else(|| "?".into());
+        let host = if self.full_hostname {
+            &host
+        } else {
+            short_hostname(&host)
+        };
+        if uzers::get_effective_uid() == 0 {
+            p.new_part(self.root_bg);
+            p.style(self.root_sty);
+        } else if ssh {
+            p.new_part(self.ssh_bg);
+            p.style(self.ssh_sty);
+        } else {
+            p.new_part(self.bg);
+            p.style(self.sty);
+        }
+        write!(p, "{}{}{}", user, self.separator, host);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_hostnames() {
+        assert_eq!(short_hostname("box.example.com"), "box");
+        assert_eq!(short_hostname("box"), "box");
+    }
+
+    #[test]
+    fn show_when_needed() {
+        let conf = UserHost {
+            show_always: false,
+            default_user: "me".into(),
+            ..Default::default()
+        };
+        assert!(!conf.should_show("me", false));
+        assert!(conf.should_show("me", true));
+        assert!(conf.should_show("root", false));
+    }
+}
@@ -1,9 +1,9 @@
 use std::borrow::Cow;
-use std::env;
 use std::mem;
-use std::path::{Component, Path, PathBuf};
+use std::path::{Component, Path};
 
-use crate::{Prompt, WorkDir};
+use crate::parts::{format_count, working_dir, Segment};
+use crate::{Color, Prompt, SegmentKind, WorkDir, WorkDirGit};
 
 mod git;
 
@@ -35,68 +35,124 @@ impl<'a> Part<'a> {
         self.count_chars(conf).min(conf.comp_max_len)
     }
 
-    /// Write the part to the prompt.
-    fn write(&self, p: &mut Prompt) {
-        fn write_truncated_str(s: &str, trun: &str, len: usize) {
+    /// Whether or not this part is a component of the path.
+    fn is_path(&self) -> bool {
+        !matches!(self, Part::Git(_))
+    }
+
+    /// Write the part to the prompt, after the part `prev`.
+    fn write(&self, p: &mut Prompt, prev: Option<&Part>) {
+        // In plain style, components of the path are joined by slashes
+        fn begin_path(p: &mut Prompt, bg: Color, prev: Option<&Part>) {
+            match prev {
+                Some(prev) if prev.is_path() => {
+                    if p.join_part(bg) && *prev != Part::Root {
+                        write!(p, "/");
+                    }
+                }
+                _ => p.new_part(bg),
+            }
+        }
+        fn write_truncated_str(p: &mut Prompt, s: &str, trun: &str, len: usize) {
             if s.chars().count() > len {
                 let n = len - trun.chars().count();
-                print!("{:.*}{}", n, s, trun);
+                write!(p, "{:.*}{}", n, s, trun);
             } else {
-                print!("{}", s);
+                write!(p, "{}", s);
             }
         }
         match self {
             Part::Truncate => {
-                p.new_part(p.conf.work_dir.path_trun_bg);
+                begin_path(p, p.conf.work_dir.path_trun_bg, prev);
                 p.style(p.conf.work_dir.path_trun_sty);
-                print!("{}", p.conf.work_dir.path_trun);
+                write!(p, "{}", p.conf.work_dir.path_trun);
             }
             Part::Root => {
-                p.new_part(p.conf.work_dir.dir_bg);
+                begin_path(p, p.conf.work_dir.dir_bg, prev);
                 p.style(p.conf.work_dir.dir_sty);
-                print!("/");
+                write!(p, "/");
             }
             Part::RootStem => {
-                p.new_part(p.conf.work_dir.base_bg);
+                begin_path(p, p.conf.work_dir.base_bg, prev);
                 p.style(p.conf.work_dir.base_sty);
-                print!("/");
+                write!(p, "/");
             }
             Part::Dir(d) => {
-                p.new_part(p.conf.work_dir.dir_bg);
+                begin_path(p, p.conf.work_dir.dir_bg, prev);
                 p.style(p.conf.work_dir.dir_sty);
-                write_truncated_str(d, &p.conf.work_dir.comp_trun, p.conf.work_dir.comp_max_len);
+                write_truncated_str(
+                    p,
+                    d,
+                    &p.conf.work_dir.comp_trun,
+                    p.conf.work_dir.comp_max_len,
+                );
             }
             Part::Stem(d) => {
-                p.new_part(p.conf.work_dir.base_bg);
+                begin_path(p, p.conf.work_dir.base_bg, prev);
                 p.style(p.conf.work_dir.base_sty);
-                write_truncated_str(d, &p.conf.work_dir.comp_trun, p.conf.work_dir.comp_max_len);
+                write_truncated_str(
+                    p,
+                    d,
+                    &p.conf.work_dir.comp_trun,
+                    p.conf.work_dir.comp_max_len,
+                );
             }
             Part::Git(s) => {
                 p.new_part(p.conf.work_dir.git.bg);
                 p.style(p.conf.work_dir.git.sty);
-                print!("{}", p.conf.work_dir.git.prefix);
+                write!(p, "{}", p.conf.work_dir.git.prefix);
+                if s.detached {
+                    p.style(p.conf.work_dir.git.detached_sty);
+                    write!(p, "{}", p.conf.work_dir.git.detached_prefix);
+                }
                 write_truncated_str(
+                    p,
                     &s.branch,
                     &p.conf.work_dir.comp_trun,
                     p.conf.work_dir.comp_max_len,
                 );
+                if s.detached {
+                    p.style(p.conf.work_dir.git.sty);
+                }
+                if let Some(op) = s.operation {
+                    let git = &p.conf.work_dir.git;
+                    let (label, sty) = match op {
+                        git::Operation::Rebase(_) => (&git.rebase, git.rebase_sty),
+                        git::Operation::Merge => (&git.merge, git.merge_sty),
+                        git::Operation::CherryPick => (&git.cherry_pick, git.cherry_pick_sty),
+                        git::Operation::Bisect => (&git.bisect, git.bisect_sty),
+                        git::Operation::Revert => (&git.revert, git.revert_sty),
+                    };
+                    write!(p, "{}", git.separator);
+                    p.style(sty);
+                    write!(p, "{}", label);
+                    if let git::Operation::Rebase(Some((step, total))) = op {
+                        write!(p, " {}/{}", step, total);
+                    }
+                    p.style(p.conf.work_dir.git.sty);
+                }
                 if !s.is_clean_and_up_to_date() {
-                    print!("{}", p.conf.work_dir.git.separator);
+                    write!(p, "{}", p.conf.work_dir.git.separator);
                     macro_rules! write_numbers {
-                        ($($conf_str:ident, $conf_sty:ident => $value:expr;)*) => {{
+                        ($($conf_str:ident, $conf_fmt:ident, $conf_sty:ident => $value:expr;)*) => {{
                             $(if $value != 0 {
-                                p.style(p.conf.work_dir.git.$conf_sty);
-                                print!("{}", p.conf.work_dir.git.$conf_str);
+                                let git = &p.conf.work_dir.git;
+                                p.style(git.$conf_sty);
+                                match &git.$conf_fmt {
+                                    Some(format) => write!(p, "{}", format_count(format, $value)),
+                                    None => write!(p, "{}", git.$conf_str),
+                                }
                             })*
                         }}
                     }
                     write_numbers! {
-                        ahead, ahead_sty => s.commits_ahead;
-                        behind, behind_sty => s.commits_behind;
-                        conflict, conflict_sty => s.conflicts;
-                        index, index_sty => s.index_changes;
-                        wt, wt_sty => s.wt_changes;
-                        untracked, untracked_sty => s.untracked;
+                        ahead, ahead_format, ahead_sty => s.commits_ahead;
+                        behind, behind_format, behind_sty => s.commits_behind;
+                        conflict, conflict_format, conflict_sty => s.conflicts;
+                        index, index_format, index_sty => s.index_changes;
+                        wt, wt_format, wt_sty => s.wt_changes;
+                        untracked, untracked_format, untracked_sty => s.untracked;
+                        stash, stash_format, stash_sty => s.stashes;
                     };
                 }
             }
@@ -104,8 +160,14 @@ impl<'a> Part<'a> {
     }
 }
 
-/// Turn a path into a list of parts.
-fn process_path<'a>(path: &'a Path, mod_path: &'a Path, conf: &WorkDir) -> Vec<Part<'a>> {
+/// Turn a path into a list of parts, with the Git branch of each
+/// repository along it if `git` is set.
+fn process_path<'a>(
+    path: &'a Path,
+    mod_path: &'a Path,
+    conf: &WorkDir,
+    git: bool,
+) -> Vec<Part<'a>> {
     fn normal_path_component_eq(comp: Component, value: &str) -> bool {
         if let Component::Normal(dir) = comp {
             dir == value
@@ -138,8 +200,8 @@ fn process_path<'a>(path: &'a Path, mod_path: &'a Path, conf: &WorkDir) -> Vec<P
     for component in mod_path.components().rev() {
         let full_path = current_path.unwrap();
         // Show git branch if enabled
-        if conf.git.enable && !normal_path_component_eq(component, ".git") {
-            match git::get_status(full_path, conf.git.status) {
+        if git && !normal_path_component_eq(component, ".git") {
+            match git::get_status(full_path, conf.git.status, conf.git.describe) {
                 Ok(Some(status)) => {
                     let part = Part::Git(status);
                     if try_add_part(part) {
@@ -191,7 +253,7 @@ fn process_path<'a>(path: &'a Path, mod_path: &'a Path, conf: &WorkDir) -> Vec<P
     parts
 }
 
-fn apply_aliases<I, P, Q>(path: &Path, aliases: I) -> Cow<Path>
+fn apply_aliases<I, P, Q>(path: &Path, aliases: I) -> Cow<'_, Path>
 where
     I: IntoIterator<Item = (P, Q)>,
     P: AsRef<str>,
@@ -220,23 +282,57 @@ where
 }
 
 fn print_parts(parts: &[Part], p: &mut Prompt) {
+    let mut prev = None;
     for part in parts {
-        part.write(p);
+        part.write(p, prev);
+        prev = Some(part);
     }
 }
 
-pub fn work_dir(p: &mut Prompt) {
-    let dir: PathBuf = env::var_os("PWD")
-        .map(|s| s.into())
-        .unwrap_or_else(|| env::current_dir().expect("cannot get working directory"));
-    let mod_path = apply_aliases(&dir, &p.conf.work_dir.aliases);
-    let parts = process_path(&dir, &mod_path, &p.conf.work_dir);
-    print_parts(&parts, p);
+impl WorkDir {
+    /// Writes the path of a directory.
+    pub fn write_path(&self, p: &mut Prompt, dir: &Path) {
+        let mod_path = apply_aliases(dir, &self.aliases);
+        // The standalone segment replaces the branch within the path
+        let git = self.git.enable && !p.conf.in_layout(&SegmentKind::Git);
+        let parts = process_path(dir, &mod_path, self, git);
+        print_parts(&parts, p);
+    }
+}
+
+impl Segment for WorkDir {
+    fn render(&self, p: &mut Prompt) {
+        self.write_path(p, &working_dir());
+    }
+}
+
+impl WorkDirGit {
+    /// Writes the branch and status of the nearest repository
+    /// containing a directory.
+    fn write_repo(&self, p: &mut Prompt, dir: &Path) {
+        for path in dir.ancestors() {
+            match git::get_status(path, self.status, self.describe) {
+                Ok(Some(status)) => return Part::Git(status).write(p, None),
+                Ok(None) => (),
+                Err(e) => {
+                    eprintln!("promptress: git ({}): {}", path.display(), e);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Segment for WorkDirGit {
+    fn render(&self, p: &mut Prompt) {
+        self.write_repo(p, &working_dir());
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Config;
     use std::collections::HashMap;
 
     #[test]
@@ -244,7 +340,7 @@ mod tests {
         let path = Path::new("/home/user/foo");
         let path_aliased = Path::new("User/foo");
         assert_eq!(
-            process_path(path, path_aliased, &Default::default()),
+            process_path(path, path_aliased, &Default::default(), false),
             vec![Part::Dir("User".into()), Part::Stem("foo".into())]
         );
     }
@@ -253,7 +349,7 @@ mod tests {
     fn process_path_absolute() {
         let path = Path::new("/home/user/foo");
         assert_eq!(
-            process_path(path, path, &Default::default()),
+            process_path(path, path, &Default::default(), false),
             vec![
                 Part::Root,
                 Part::Dir("home".into()),
@@ -267,7 +363,7 @@ mod tests {
     fn process_path_special_parts() {
         let path = Path::new("./foo/../bar");
         assert_eq!(
-            process_path(path, path, &Default::default()),
+            process_path(path, path, &Default::default(), false),
             vec![
                 Part::Dir(".".into()),
                 Part::Dir("foo".into()),
@@ -281,7 +377,7 @@ mod tests {
     fn process_path_root() {
         let path = Path::new("/");
         assert_eq!(
-            process_path(path, path, &Default::default()),
+            process_path(path, path, &Default::default(), false),
             vec![Part::RootStem]
         );
     }
@@ -289,10 +385,12 @@ mod tests {
     #[test]
     fn process_path_truncate() {
         let path = Path::new("/one/two/three/four/five/six/seven");
-        let mut conf = WorkDir::default();
-        conf.path_max_len = 10;
+        let conf = WorkDir {
+            path_max_len: 10,
+            ..Default::default()
+        };
         assert_eq!(
-            process_path(path, path, &conf),
+            process_path(path, path, &conf, false),
             vec![Part::Truncate, Part::Stem("seven".into())]
         );
     }
@@ -307,7 +405,7 @@ mod tests {
         let path = Path::new(OsStr::from_bytes(b"/foo/\xff"));
         let conf = WorkDir::default();
         assert_eq!(
-            process_path(path, path, &conf),
+            process_path(path, path, &conf, false),
             vec![
                 Part::Root,
                 Part::Dir("foo".into()),
@@ -316,6 +414,71 @@ mod tests {
         );
     }
 
+    fn render_part(part: &Part) -> String {
+        Prompt::render_with(Config::default(), |p| part.write(p, None))
+    }
+
+    #[test]
+    fn git_operation() {
+        let part = Part::Git(git::GitStatus {
+            branch: "main".into(),
+            operation: Some(git::Operation::Rebase(Some((3, 7)))),
+            ..Default::default()
+        });
+        assert_eq!(
+            render_part(&part),
+            "\x1b[0;48;5;250m \x1b[0;48;5;250;30mGit:main|\x1b[0;48;5;250;38;5;88;1mREBASE 3/7\
+             \x1b[0;48;5;250;30m \x1b[0m"
+        );
+        let part = Part::Git(git::GitStatus {
+            branch: "main".into(),
+            wt_changes: 1,
+            operation: Some(git::Operation::Merge),
+            ..Default::default()
+        });
+        assert!(render_part(&part).contains("MERGING\x1b[0;48;5;250;30m|\x1b[0;48;5;250;38;5;88m+"));
+    }
+
+    #[test]
+    fn git_counts() {
+        let mut config = Config::default();
+        config.work_dir.git.behind_format = Some("↓{n}".into());
+        let part = Part::Git(git::GitStatus {
+            branch: "main".into(),
+            commits_ahead: 2,
+            commits_behind: 300,
+            ..Default::default()
+        });
+        let out = Prompt::render_with(config, |p| part.write(p, None));
+        assert!(out.contains("m↑\x1b[0;48;5;250;38;5;19m↓300 "));
+    }
+
+    #[test]
+    fn standalone_git_segment() {
+        let dir = std::env::temp_dir().join(format!("promptress-seg-{}", std::process::id()));
+        let sub = dir.join("sub");
+        std::fs::create_dir_all(&sub).unwrap();
+        let repo = git2::Repository::init(&dir).unwrap();
+        repo.set_head("refs/heads/trunk").unwrap();
+
+        let mut work_dir = WorkDir::default();
+        work_dir.git.enable = true;
+        let git = Prompt::render_with(Config::default(), |p| work_dir.git.write_repo(p, &sub));
+        assert!(git.contains("Git:trunk"));
+
+        // The working directory shows the branch, unless the segment
+        // is in a layout
+        let path = Prompt::render_with(Config::default(), |p| work_dir.write_path(p, &sub));
+        assert!(path.contains("Git:trunk"));
+        let conf = Config {
+            right_layout: vec![SegmentKind::Git],
+            ..Default::default()
+        };
+        let path = Prompt::render_with(conf, |p| work_dir.write_path(p, &sub));
+        assert!(!path.contains("Git:"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn apply_alias_no_match() {
         let path = Path::new("/no/match");
@@ -1,12 +1,92 @@
 use std::borrow::Cow;
+use std::fs;
 use std::path::Path;
 
+use git2::{
+    BranchType, DescribeOptions, Error, ErrorCode, Reference, Repository, RepositoryState,
+    StatusOptions,
+};
 use if_chain::if_chain;
-use git2::{BranchType, Error, ErrorCode, Repository, StatusOptions};
+
+/// Operation in progress in a repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Rebase, with the current step and the total number of steps if
+    /// they are known
+    Rebase(Option<(u32, u32)>),
+    Merge,
+    CherryPick,
+    Bisect,
+    Revert,
+}
+
+impl Operation {
+    fn from_state(state: RepositoryState, git_dir: &Path) -> Option<Operation> {
+        match state {
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => {
+                Some(Operation::Rebase(rebase_step(git_dir)))
+            }
+            RepositoryState::Merge => Some(Operation::Merge),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(Operation::CherryPick)
+            }
+            RepositoryState::Bisect => Some(Operation::Bisect),
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(Operation::Revert),
+            RepositoryState::Clean | RepositoryState::ApplyMailbox => None,
+        }
+    }
+}
+
+/// Reads the current step and the total number of steps of a rebase.
+fn rebase_step(git_dir: &Path) -> Option<(u32, u32)> {
+    let read = |dir: &str, file: &str| -> Option<u32> {
+        fs::read_to_string(git_dir.join(dir).join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    // `rebase-merge` is used by interactive and merge-based rebases,
+    // and `rebase-apply` by apply-based ones
+    read("rebase-merge", "msgnum")
+        .zip(read("rebase-merge", "end"))
+        .or_else(|| read("rebase-apply", "next").zip(read("rebase-apply", "last")))
+}
+
+/// Names a detached HEAD after a tag pointing to it, or the nearest
+/// tag if `describe` is set, or otherwise its abbreviated commit ID.
+fn describe_head(repo: &Repository, head: &Reference, describe: bool) -> Result<String, Error> {
+    let mut opts = DescribeOptions::new();
+    opts.describe_tags();
+    if !describe {
+        // Looking for the nearest tag walks the history, which can be
+        // slow, so only accept an exact match
+        opts.max_candidates_tags(0);
+    }
+    if let Ok(d) = repo.describe(&opts) {
+        return d.format(None);
+    }
+    let id = head.peel_to_commit()?.as_object().short_id()?;
+    Ok(id.as_str().unwrap_or_default().into())
+}
+
+/// Returns the name of the branch HEAD points to, before its first
+/// commit.
+fn unborn_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    Some(target.trim_start_matches("refs/heads/").into())
+}
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct GitStatus {
+    /// Branch name, or the name of the commit if HEAD is detached
     pub branch: Cow<'static, str>,
+    /// Whether or not HEAD is detached
+    pub detached: bool,
     /// Number of commits ahead of remote
     pub commits_ahead: u32,
     /// Number of commits behind remote
@@ -19,6 +99,10 @@ pub struct GitStatus {
     pub untracked: u32,
     /// Number of files conflicted
     pub conflicts: u32,
+    /// Number of stash entries
+    pub stashes: u32,
+    /// Operation in progress, such as a rebase
+    pub operation: Option<Operation>,
 }
 
 impl GitStatus {
@@ -29,18 +113,32 @@ impl GitStatus {
             && self.wt_changes == 0
             && self.untracked == 0
             && self.conflicts == 0
+            && self.stashes == 0
     }
 }
 
-pub fn get_status(path: &Path, status: bool) -> Result<Option<GitStatus>, Error> {
+pub fn get_status(path: &Path, status: bool, describe: bool) -> Result<Option<GitStatus>, Error> {
     let mut s = GitStatus::default();
 
-    let repo = match Repository::open(path) {
+    // Counting stashes needs a mutable repository
+    let mut repo = match Repository::open(path) {
         Ok(r) => r,
         Err(_) => return Ok(None),
     };
+    if status {
+        // repo.stash_foreach() could fail, e.g. in a bare repo
+        let _ = repo.stash_foreach(|_, _, _| {
+            s.stashes += 1;
+            true
+        });
+    }
+    s.operation = Operation::from_state(repo.state(), repo.path());
     let head = repo.head();
     s.branch = match head {
+        Ok(ref h) if repo.head_detached()? => {
+            s.detached = true;
+            describe_head(&repo, h, describe)?.into()
+        }
         Ok(h) => {
             if let Some(branch_name) = h.shorthand() {
                 if_chain! {
@@ -60,7 +158,10 @@ pub fn get_status(path: &Path, status: bool) -> Result<Option<GitStatus>, Error>
                 "--".into()
             }
         }
-        Err(ref e) if e.code() == ErrorCode::UnbornBranch => "--".into(),
+        Err(ref e) if e.code() == ErrorCode::UnbornBranch => match unborn_branch(&repo) {
+            Some(name) => name.into(),
+            None => "--".into(),
+        },
         Err(e) => return Err(e),
     };
 
@@ -100,3 +201,87 @@ pub fn get_status(path: &Path, status: bool) -> Result<Option<GitStatus>, Error>
 
     Ok(Some(s))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn rebase_steps() {
+        let dir = env::temp_dir().join(format!("promptress-git-{}", process::id()));
+        assert_eq!(rebase_step(&dir), None);
+        fs::create_dir_all(dir.join("rebase-apply")).unwrap();
+        fs::write(dir.join("rebase-apply/next"), "2\n").unwrap();
+        fs::write(dir.join("rebase-apply/last"), "5\n").unwrap();
+        assert_eq!(rebase_step(&dir), Some((2, 5)));
+        fs::create_dir_all(dir.join("rebase-merge")).unwrap();
+        fs::write(dir.join("rebase-merge/msgnum"), "3\n").unwrap();
+        fs::write(dir.join("rebase-merge/end"), "7\n").unwrap();
+        assert_eq!(rebase_step(&dir), Some((3, 7)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detached_and_unborn() {
+        let dir = env::temp_dir().join(format!("promptress-detached-{}", process::id()));
+        let repo = Repository::init(&dir).unwrap();
+        repo.set_head("refs/heads/trunk").unwrap();
+        let status = get_status(&dir, false, true).unwrap().unwrap();
+        assert_eq!(status.branch, "trunk");
+        assert!(!status.detached);
+
+        let sig = git2::Signature::now("a", "a@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let id = repo.commit(None, &sig, &sig, "x", &tree, &[]).unwrap();
+        repo.set_head_detached(id).unwrap();
+        let status = get_status(&dir, false, true).unwrap().unwrap();
+        assert!(status.detached);
+        assert_eq!(status.branch, &id.to_string()[..7]);
+
+        let commit = repo.find_object(id, None).unwrap();
+        repo.tag_lightweight("v1.0", &commit, false).unwrap();
+        let status = get_status(&dir, false, false).unwrap().unwrap();
+        assert_eq!(status.branch, "v1.0");
+
+        // The nearest tag is only looked for if enabled
+        let parent = repo.find_commit(id).unwrap();
+        let id = repo
+            .commit(None, &sig, &sig, "y", &tree, &[&parent])
+            .unwrap();
+        repo.set_head_detached(id).unwrap();
+        let status = get_status(&dir, false, false).unwrap().unwrap();
+        assert_eq!(status.branch, &id.to_string()[..7]);
+        let status = get_status(&dir, false, true).unwrap().unwrap();
+        assert_eq!(status.branch, format!("v1.0-1-g{}", &id.to_string()[..7]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stash_count() {
+        let dir = env::temp_dir().join(format!("promptress-stash-{}", process::id()));
+        let mut repo = Repository::init(&dir).unwrap();
+        let sig = git2::Signature::now("a", "a@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        fs::write(dir.join("file"), "1").unwrap();
+        index.add_path(Path::new("file")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "x", &tree, &[])
+            .unwrap();
+        drop(tree);
+        for i in 2..4 {
+            fs::write(dir.join("file"), i.to_string()).unwrap();
+            repo.stash_save(&sig, "wip", None).unwrap();
+        }
+        let status = get_status(&dir, true, true).unwrap().unwrap();
+        assert_eq!(status.stashes, 2);
+        assert!(!status.is_clean_and_up_to_date());
+        let status = get_status(&dir, false, true).unwrap().unwrap();
+        assert_eq!(status.stashes, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
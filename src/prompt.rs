@@ -1,111 +1,731 @@
-use crate::{Config, Style};
+use std::fmt;
+use std::fmt::Write;
 
-pub struct Prompt {
-    pub conf: Config,
-    last_bg: Option<u8>,
+use crate::parts;
+use crate::{Color, Config, RenderStyle, SegmentKind, Shell, Style};
+
+/// Returns the strings which mark the beginning and end of a
+/// non-printing sequence, so that the shell can compute the width of
+/// the prompt.
+fn markers(shell: Shell) -> (&'static str, &'static str) {
+    match shell {
+        Shell::Bash => ("\x01", "\x02"),
+        Shell::BashPs1 => ("\\[", "\\]"),
+        Shell::Zsh | Shell::Tcsh => ("%{", "%}"),
+        Shell::Fish | Shell::Powershell | Shell::None => ("", ""),
+    }
+}
+
+/// Writes text to a `String`, escaping characters which the shell
+/// would otherwise interpret.
+struct Escaper<'a> {
+    out: &'a mut String,
+    shell: Shell,
+}
+
+impl fmt::Write for Escaper<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Shell::Bash | Shell::Fish | Shell::Powershell | Shell::None = self.shell {
+            self.out.push_str(s);
+            return Ok(());
+        }
+        for c in s.chars() {
+            match (self.shell, c) {
+                // Bash decodes `\\` to `\`, then expands the prompt as if
+                // in double quotes, so quote the characters that would
+                // be expanded the same way bash quotes `\w`
+                (Shell::BashPs1, '\\') => self.out.push_str("\\\\\\\\"),
+                (Shell::BashPs1, '$') | (Shell::BashPs1, '`') | (Shell::BashPs1, '"') => {
+                    self.out.push_str("\\\\");
+                    self.out.push(c);
+                }
+                (Shell::Zsh, '%') | (Shell::Tcsh, '%') => self.out.push_str("%%"),
+                // `!` is the history number in tcsh
+                (Shell::Tcsh, '!') => self.out.push_str("\\!"),
+                _ => self.out.push(c),
+            }
+        }
+        Ok(())
+    }
 }
 
-impl Prompt {
-    pub fn new(conf: Config) -> Self {
+pub struct Prompt<'a> {
+    pub conf: &'a Config,
+    /// Whether this is a right-hand prompt, with reversed arrows.
+    right: bool,
+    last_bg: Option<Color>,
+    /// Whether the next part starts a new segment, which begins a new
+    /// group in plain style.
+    segment_start: bool,
+    /// Number of line breaks left in the layout being rendered.
+    newlines: usize,
+    /// Rendered output, written out all at once by the caller.
+    out: String,
+}
+
+impl<'a> Prompt<'a> {
+    pub fn new(conf: &'a Config) -> Self {
         Prompt {
             conf,
+            right: false,
             last_bg: None,
+            segment_start: false,
+            newlines: 0,
+            out: String::new(),
+        }
+    }
+
+    /// Creates a right-hand prompt, whose arrows point to the left.
+    pub fn new_right(conf: &'a Config) -> Self {
+        Prompt {
+            right: true,
+            ..Prompt::new(conf)
         }
     }
+
+    /// Returns the rendered prompt.
+    pub fn into_string(self) -> String {
+        self.out
+    }
+}
+
+impl Prompt<'_> {
+    /// Appends formatted text to the prompt. This lets parts use
+    /// `write!(p, ...)` in the same way as `print!`.
+    pub fn write_fmt(&mut self, args: fmt::Arguments) {
+        let mut escaper = Escaper {
+            out: &mut self.out,
+            shell: self.conf.shell,
+        };
+        // Writing to a `String` cannot fail
+        let _ = escaper.write_fmt(args);
+    }
 }
 
-impl Prompt {
+impl Prompt<'_> {
     #[inline]
     fn color_begin(&mut self) {
-        print!("\x01\x1b[0");
+        self.out.push_str(markers(self.conf.shell).0);
+        write!(self, "\x1b[0");
     }
 
     #[inline]
     fn color_end(&mut self) {
-        print!("m\x02");
+        write!(self, "m");
+        self.out.push_str(markers(self.conf.shell).1);
     }
 
     #[inline]
-    fn color_fg(&mut self, fg: u8) {
+    fn color_fg(&mut self, fg: Color) {
         match fg {
-            0..=7 => print!(";{}", 30 + fg),
-            8..=15 => print!(";{}", 90 - 8 + fg),
-            _ => print!(";38;5;{}", fg),
+            Color::Fixed(n @ 0..=7) => write!(self, ";{}", 30 + n),
+            Color::Fixed(n @ 8..=15) => write!(self, ";{}", 90 - 8 + n),
+            Color::Fixed(n) => write!(self, ";38;5;{}", n),
+            Color::Rgb(r, g, b) => write!(self, ";38;2;{};{};{}", r, g, b),
+            Color::Default => write!(self, ";39"),
         }
     }
 
     #[inline]
-    fn color_bg(&mut self, bg: u8) {
+    fn color_bg(&mut self, bg: Color) {
         match bg {
-            0..=7 => print!(";{}", 40 + bg),
-            8..=15 => print!(";{}", 100 - 8 + bg),
-            _ => print!(";48;5;{}", bg),
+            Color::Fixed(n @ 0..=7) => write!(self, ";{}", 40 + n),
+            Color::Fixed(n @ 8..=15) => write!(self, ";{}", 100 - 8 + n),
+            Color::Fixed(n) => write!(self, ";48;5;{}", n),
+            Color::Rgb(r, g, b) => write!(self, ";48;2;{};{};{}", r, g, b),
+            Color::Default => write!(self, ";49"),
         }
     }
 
-    pub fn style(&mut self, style: Style) {
+    pub fn style(&mut self, mut style: Style) {
         self.color_begin();
-        self.color_bg(self.last_bg.unwrap());
+        match self.conf.style {
+            RenderStyle::Powerline => self.color_bg(self.last_bg.unwrap()),
+            RenderStyle::Plain => {
+                // Text in the color of the part's background is meant to
+                // be drawn on a different background, which is not drawn
+                // in this style, so use the terminal's own color instead
+                if Some(style.color) == self.last_bg {
+                    style.color = Color::Default;
+                }
+            }
+        }
+        self.style_codes(style);
+        self.color_end();
+    }
+
+    /// Writes the codes for a foreground style.
+    fn style_codes(&mut self, style: Style) {
         self.color_fg(style.color);
         if style.bold {
-            print!(";1");
+            write!(self, ";1");
         }
         if style.italic {
-            print!(";3");
+            write!(self, ";3");
         }
         if style.underline {
-            print!(";4");
+            write!(self, ";4");
         }
         if style.blink {
-            print!(";5");
+            write!(self, ";5");
         }
         if style.strike {
-            print!(";9");
+            write!(self, ";9");
         }
-        self.color_end();
     }
 
-    fn bg(&mut self, bg: u8) {
+    fn bg(&mut self, bg: Color) {
         self.color_begin();
         self.color_bg(bg);
         self.color_end();
     }
+
+    /// Resets to the default colors.
+    fn reset(&mut self) {
+        self.color_begin();
+        self.color_end();
+    }
 }
 
-impl Prompt {
-    pub fn new_part(&mut self, bg: u8) {
+impl<'a> Prompt<'a> {
+    /// Returns a configured separator, or the default for the current
+    /// render style if it is not set.
+    fn sep(
+        &self,
+        value: &'a Option<String>,
+        powerline: &'static str,
+        plain: &'static str,
+    ) -> &'a str {
+        match (value, self.conf.style) {
+            (Some(value), _) => value,
+            (None, RenderStyle::Powerline) => powerline,
+            (None, RenderStyle::Plain) => plain,
+        }
+    }
+
+    pub fn new_part(&mut self, bg: Color) {
+        match self.conf.style {
+            RenderStyle::Powerline => self.new_powerline_part(bg),
+            RenderStyle::Plain => self.new_plain_part(),
+        }
+        self.last_bg = Some(bg);
+        self.segment_start = false;
+    }
+
+    /// Starts a part which continues the text of the previous one, such
+    /// as the next component of a path. In plain style, nothing is
+    /// written between them. Returns whether or not the parts were
+    /// joined.
+    pub fn join_part(&mut self, bg: Color) -> bool {
+        if self.conf.style == RenderStyle::Plain && self.last_bg.is_some() && !self.segment_start {
+            self.reset();
+            self.last_bg = Some(bg);
+            true
+        } else {
+            self.new_part(bg);
+            false
+        }
+    }
+
+    /// Marks the start of a segment. In plain style, each segment is
+    /// drawn as its own group.
+    pub fn begin_segment(&mut self) {
+        self.segment_start = true;
+    }
+
+    fn new_powerline_part(&mut self, bg: Color) {
+        let seps = &self.conf.separators;
+        let padding = self.sep(&seps.padding, " ", "");
         match self.last_bg {
             None => {
-                // First part
+                // First part. A right-hand prompt starts with an arrow
+                // unless there is a cap. Neither is needed to blend into
+                // the terminal's background color.
+                let cap = match (&seps.left_cap, self.right) {
+                    (None, true) => self.sep(&seps.right_change, "\u{e0b2}", " "),
+                    _ => self.sep(&seps.left_cap, "", ""),
+                };
+                if !cap.is_empty() && bg != Color::Default {
+                    self.color_begin();
+                    self.color_fg(bg);
+                    self.color_bg(Color::Default);
+                    self.color_end();
+                    write!(self, "{}", cap);
+                }
                 self.bg(bg);
-                print!(" ");
+                write!(self, "{}", padding);
             }
             Some(last_bg) if last_bg == bg => {
                 // Same color part
-                self.style(Style::color(0));
-                print!(" \u{e0b1} ");
+                let sep_color = match bg {
+                    Color::Default => Color::Default,
+                    _ => Color::Fixed(0),
+                };
+                let same = if self.right {
+                    self.sep(&seps.right_same, "\u{e0b3}", " ")
+                } else {
+                    self.sep(&seps.same, "\u{e0b1}", " ")
+                };
+                self.style(Style::color(sep_color));
+                write!(self, "{}{}{}", padding, same, padding);
             }
             Some(last_bg) => {
-                // Different color part
-                print!(" ");
+                // Different color part. The arrow is drawn in the color
+                // of the part that it points away from.
+                let (change, arrow_fg, arrow_bg) = if self.right {
+                    let change = self.sep(&seps.right_change, "\u{e0b2}", " ");
+                    (change, bg, last_bg)
+                } else {
+                    let change = self.sep(&seps.change, "\u{e0b0}", " ");
+                    (change, last_bg, bg)
+                };
+                write!(self, "{}", padding);
                 self.color_begin();
-                self.color_fg(last_bg);
-                self.color_bg(bg);
+                if arrow_fg == Color::Default {
+                    // The terminal's background color has no code of its
+                    // own, so draw the arrow in it using reverse video
+                    self.color_fg(arrow_bg);
+                    self.color_bg(Color::Default);
+                    write!(self, ";7");
+                } else {
+                    self.color_fg(arrow_fg);
+                    self.color_bg(arrow_bg);
+                }
                 self.color_end();
-                print!("\u{e0b0}");
+                write!(self, "{}", change);
                 self.bg(bg);
-                print!(" ");
+                write!(self, "{}", padding);
             }
         }
-        self.last_bg = Some(bg);
+    }
+
+    fn new_plain_part(&mut self) {
+        let seps = &self.conf.separators;
+        let padding = self.sep(&seps.padding, " ", "");
+        let left_cap = self.sep(&seps.left_cap, "", "");
+        // Separators and caps are drawn in the default colors
+        self.reset();
+        match self.last_bg {
+            None => {
+                // First part
+                write!(self, "{}{}", left_cap, padding);
+            }
+            Some(_) if !self.segment_start => {
+                // Part of the same segment
+                let same = self.sep(&seps.same, "\u{e0b1}", " ");
+                write!(self, "{}{}{}", padding, same, padding);
+            }
+            Some(_) => {
+                // First part of a segment, which starts a new group
+                let right_cap = self.sep(&seps.right_cap, "", "");
+                let change = self.sep(&seps.change, "\u{e0b0}", " ");
+                write!(
+                    self,
+                    "{}{}{}{}{}",
+                    padding, right_cap, change, left_cap, padding
+                );
+            }
+        }
+    }
+
+    /// Renders the segments in `layout`, then finishes the prompt.
+    pub fn render(&mut self, layout: &[SegmentKind]) {
+        let conf = self.conf;
+        self.newlines = layout
+            .iter()
+            .filter(|&kind| *kind == SegmentKind::Newline)
+            .count();
+        if self.newlines > 0 {
+            self.connector(&conf.newline.first);
+        }
+        for kind in layout {
+            self.begin_segment();
+            parts::segment(conf, kind).render(self);
+        }
+        self.finish();
+    }
+
+    /// Finishes the current line and starts a new one.
+    pub fn newline(&mut self) {
+        let conf = self.conf;
+        self.finish();
+        writeln!(self);
+        self.last_bg = None;
+        self.newlines -= 1;
+        if self.newlines == 0 {
+            self.connector(&conf.newline.last);
+        } else {
+            self.connector(&conf.newline.middle);
+        }
+    }
+
+    /// Writes a connector at the start of a line.
+    fn connector(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        self.color_begin();
+        self.style_codes(self.conf.newline.sty);
+        self.color_end();
+        write!(self, "{}", s);
+        self.reset();
     }
 
     pub fn finish(&mut self) {
-        if self.last_bg.is_some() {
-            print!(" ");
-            // Reset color
-            self.color_begin();
-            self.color_end();
+        let seps = &self.conf.separators;
+        let padding = self.sep(&seps.padding, " ", "");
+        let right_cap = self.sep(&seps.right_cap, "", "");
+        let last_bg = match self.last_bg {
+            Some(bg) => bg,
+            None => return,
+        };
+        match self.conf.style {
+            RenderStyle::Powerline => {
+                write!(self, "{}", padding);
+                if !right_cap.is_empty() && last_bg != Color::Default {
+                    self.color_begin();
+                    self.color_fg(last_bg);
+                    self.color_bg(Color::Default);
+                    self.color_end();
+                    write!(self, "{}", right_cap);
+                }
+            }
+            RenderStyle::Plain => {
+                self.reset();
+                write!(self, "{}{}", padding, right_cap);
+            }
         }
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+impl Prompt<'_> {
+    /// Renders a prompt without shell markers by calling `f`, then
+    /// finishes it and returns the output.
+    pub fn render_with(mut conf: Config, f: impl FnOnce(&mut Prompt)) -> String {
+        conf.shell = Shell::None;
+        let mut p = Prompt::new(&conf);
+        f(&mut p);
+        p.finish();
+        p.into_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Newline, Segment, Separators};
+    use std::path::Path;
+
+    #[test]
+    fn empty_prompt() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.finish();
+        assert_eq!(p.into_string(), "");
+    }
+
+    #[test]
+    fn same_color_parts() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(15));
+        write!(p, "a");
+        p.new_part(Color::Fixed(15));
+        write!(p, "b");
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;107m\x02 a\x01\x1b[0;107;30m\x02 \u{e0b1} b \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn different_color_parts() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        write!(p, "a");
+        p.new_part(Color::Fixed(250));
+        write!(p, "b");
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;40m\x02 a \x01\x1b[0;30;48;5;250m\x02\u{e0b0}\x01\x1b[0;48;5;250m\x02 b \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn zsh_markers_and_escaping() {
+        let conf = Config {
+            shell: Shell::Zsh,
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        write!(p, "100%");
+        p.finish();
+        assert_eq!(p.into_string(), "%{\x1b[0;40m%} 100%% %{\x1b[0m%}");
+    }
+
+    #[test]
+    fn bash_ps1_escaping() {
+        let conf = Config {
+            shell: Shell::BashPs1,
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        write!(p, "/tmp/$(echo PWNED)/`id`/\"a\\b\"");
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\\[\x1b[0;40m\\] /tmp/\\\\$(echo PWNED)/\\\\`id\\\\`/\\\\\"a\\\\\\\\b\\\\\" \\[\x1b[0m\\]"
+        );
+    }
+
+    #[test]
+    fn tcsh_escaping() {
+        let conf = Config {
+            shell: Shell::Tcsh,
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        write!(p, "100%!");
+        p.finish();
+        assert_eq!(p.into_string(), "%{\x1b[0;40m%} 100%%\\! %{\x1b[0m%}");
+    }
+
+    #[test]
+    fn fish_no_markers() {
+        let conf = Config {
+            shell: Shell::Fish,
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        write!(p, "a");
+        p.finish();
+        assert_eq!(p.into_string(), "\x1b[0;40m a \x1b[0m");
+    }
+
+    #[test]
+    fn truecolor_part() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Rgb(255, 136, 0));
+        p.style(Style::color(Color::Rgb(1, 2, 3)));
+        write!(p, "a");
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;48;2;255;136;0m\x02 \x01\x1b[0;48;2;255;136;0;38;2;1;2;3m\x02a \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn into_and_out_of_default_color() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(1));
+        p.new_part(Color::Default);
+        p.new_part(Color::Fixed(2));
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;41m\x02  \x01\x1b[0;31;49m\x02\u{e0b0}\x01\x1b[0;49m\x02  \
+             \x01\x1b[0;32;49;7m\x02\u{e0b0}\x01\x1b[0;42m\x02  \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn same_default_color() {
+        let conf = Config::default();
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Default);
+        p.new_part(Color::Default);
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;49m\x02 \x01\x1b[0;49;39m\x02 \u{e0b1}  \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn plain_style_brackets() {
+        let conf = Config {
+            style: RenderStyle::Plain,
+            separators: Separators {
+                left_cap: Some("[".into()),
+                right_cap: Some("]".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(0));
+        p.style(Style::color(1));
+        write!(p, "a");
+        p.new_part(Color::Fixed(0));
+        write!(p, "b");
+        p.begin_segment();
+        p.new_part(Color::Fixed(15));
+        write!(p, "c");
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0m\x02[\x01\x1b[0;31m\x02a\x01\x1b[0m\x02 b\
+             \x01\x1b[0m\x02] [c\x01\x1b[0m\x02]\x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn plain_default_layout() {
+        let conf = Config {
+            style: RenderStyle::Plain,
+            separators: Separators {
+                left_cap: Some("[".into()),
+                right_cap: Some("]".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // Render the segments of the default layout with fixed inputs
+        let segments = Config::default();
+        assert_eq!(
+            segments.layout,
+            [
+                SegmentKind::ExitCode,
+                SegmentKind::WorkDir,
+                SegmentKind::Dollar
+            ]
+        );
+        let out = Prompt::render_with(conf, |p| {
+            p.begin_segment();
+            segments.exit_code.write(p, Some("1"), "");
+            p.begin_segment();
+            segments.work_dir.write_path(p, Path::new("/tmp/rv/a/b"));
+            p.begin_segment();
+            segments.dollar.render(p);
+        });
+        // Each segment is a group, and the path is joined by slashes
+        assert!(out.starts_with(
+            "\x1b[0m[\x1b[0;91;1m1\x1b[0m] [\x1b[0;30m/\x1b[0m\x1b[0;30mtmp\x1b[0m/\x1b[0;30mrv\
+             \x1b[0m/\x1b[0;30ma\x1b[0m/\x1b[0;30mb\x1b[0m] [\x1b[0;"
+        ));
+        assert!(out.ends_with("\x1b[0m]\x1b[0m"));
+    }
+
+    #[test]
+    fn plain_style_same_color_as_bg() {
+        let conf = Config {
+            style: RenderStyle::Plain,
+            ..Default::default()
+        };
+        let out = Prompt::render_with(conf, |p| {
+            p.new_part(Color::Fixed(15));
+            p.style(Style::color(15));
+            write!(p, "a");
+            p.style(Style::color(0));
+            write!(p, "b");
+        });
+        assert_eq!(out, "\x1b[0m\x1b[0;39ma\x1b[0;30mb\x1b[0m\x1b[0m");
+    }
+
+    #[test]
+    fn powerline_caps_and_separators() {
+        let conf = Config {
+            separators: Separators {
+                change: Some(">".into()),
+                left_cap: Some("(".into()),
+                right_cap: Some(")".into()),
+                padding: Some("".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(1));
+        p.new_part(Color::Fixed(2));
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;31;49m\x02(\x01\x1b[0;41m\x02\x01\x1b[0;31;42m\x02>\
+             \x01\x1b[0;42m\x02\x01\x1b[0;32;49m\x02)\x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn right_prompt_arrows() {
+        let conf = Config::default();
+        let mut p = Prompt::new_right(&conf);
+        p.new_part(Color::Fixed(1));
+        p.new_part(Color::Fixed(1));
+        p.new_part(Color::Fixed(2));
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;31;49m\x02\u{e0b2}\x01\x1b[0;41m\x02 \
+             \x01\x1b[0;41;30m\x02 \u{e0b3}  \x01\x1b[0;32;41m\x02\u{e0b2}\
+             \x01\x1b[0;42m\x02  \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn no_caps_around_default_color() {
+        let conf = Config {
+            separators: Separators {
+                right_cap: Some(")".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut p = Prompt::new_right(&conf);
+        p.new_part(Color::Default);
+        p.new_part(Color::Fixed(1));
+        p.new_part(Color::Default);
+        p.finish();
+        assert_eq!(
+            p.into_string(),
+            "\x01\x1b[0;49m\x02  \x01\x1b[0;31;49m\x02\u{e0b2}\x01\x1b[0;41m\x02  \
+             \x01\x1b[0;31;49;7m\x02\u{e0b2}\x01\x1b[0;49m\x02  \x01\x1b[0m\x02"
+        );
+    }
+
+    #[test]
+    fn multiple_lines() {
+        let conf = Config {
+            shell: Shell::None,
+            newline: Newline {
+                first: "a".into(),
+                middle: "b".into(),
+                last: "c".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.render(&[SegmentKind::Newline, SegmentKind::Newline]);
+        assert_eq!(
+            p.into_string(),
+            "\x1b[0;39ma\x1b[0m\n\x1b[0;39mb\x1b[0m\n\x1b[0;39mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn newline_finishes_line() {
+        let conf = Config {
+            shell: Shell::None,
+            ..Default::default()
+        };
+        let mut p = Prompt::new(&conf);
+        p.new_part(Color::Fixed(1));
+        p.newlines = 1;
+        p.newline();
+        p.new_part(Color::Fixed(1));
+        p.finish();
+        assert_eq!(p.into_string(), "\x1b[0;41m  \x1b[0m\n\x1b[0;41m  \x1b[0m");
     }
 }